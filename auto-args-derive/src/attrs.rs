@@ -0,0 +1,316 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Parsing of the `#[auto_args(...)]` attributes.
+
+use syn::{Attribute, Expr, LitChar, LitStr, Result, Token};
+
+/// How the name of a field or variant is turned into a flag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RenameRule {
+    /// Underscores become dashes, and `CamelCase` variants become
+    /// `camel-case`.  This is the default.
+    KebabCase,
+    /// Like `KebabCase`, but using underscores rather than dashes.
+    SnakeCase,
+    /// Like `KebabCase`, but with every letter lowercased.
+    Lowercase,
+    /// The name is used exactly as written in the source.
+    Verbatim,
+}
+
+impl RenameRule {
+    fn from_lit(lit: &LitStr) -> Result<Self> {
+        match lit.value().as_str() {
+            "kebab-case" => Ok(RenameRule::KebabCase),
+            "snake_case" => Ok(RenameRule::SnakeCase),
+            "lowercase" => Ok(RenameRule::Lowercase),
+            "verbatim" => Ok(RenameRule::Verbatim),
+            _ => Err(syn::Error::new(
+                lit.span(),
+                "expected one of \"kebab-case\", \"snake_case\", \"lowercase\" or \"verbatim\"",
+            )),
+        }
+    }
+
+    /// Convert the name of a struct field into a flag name.
+    pub(crate) fn apply_to_field(self, name: &str) -> String {
+        match self {
+            RenameRule::KebabCase => name.replace('_', "-"),
+            RenameRule::SnakeCase => name.to_string(),
+            RenameRule::Lowercase => name.replace('_', "-").to_lowercase(),
+            RenameRule::Verbatim => name.to_string(),
+        }
+    }
+
+    /// Convert the name of an enum variant into a flag name.
+    pub(crate) fn apply_to_variant(self, name: &str) -> String {
+        match self {
+            RenameRule::KebabCase => camel_case_to_kebab(name),
+            RenameRule::SnakeCase => camel_case_to_kebab(name).replace('-', "_"),
+            RenameRule::Lowercase => camel_case_to_kebab(name).to_lowercase(),
+            RenameRule::Verbatim => name.to_string(),
+        }
+    }
+}
+
+/// What to do when a flag that takes a single value is given more than
+/// once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Repeated {
+    /// It is an error.  This is the default.
+    Error,
+    /// The last occurrence is used, and the others are ignored.
+    Last,
+}
+
+impl Repeated {
+    fn from_lit(lit: &LitStr) -> Result<Self> {
+        match lit.value().as_str() {
+            "error" => Ok(Repeated::Error),
+            "last" => Ok(Repeated::Last),
+            _ => Err(syn::Error::new(lit.span(), "expected \"error\" or \"last\"")),
+        }
+    }
+}
+
+fn camel_case_to_kebab(name: &str) -> String {
+    if name.contains('_') {
+        let mut out = name.replace('_', "-");
+        if out.ends_with('-') {
+            out.pop();
+        }
+        out
+    } else {
+        let mut out = String::new();
+        let mut am_on_cap = true;
+        for c in name.chars() {
+            if !am_on_cap && c.is_ascii_uppercase() {
+                out.push('-');
+            }
+            am_on_cap = c.is_ascii_uppercase();
+            out.push(c.to_ascii_lowercase());
+        }
+        out
+    }
+}
+
+/// Attributes given on the struct or enum itself.
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    pub(crate) rename_all: Option<RenameRule>,
+    pub(crate) env_prefix: Option<String>,
+    pub(crate) subcommand: bool,
+    pub(crate) repeated: Option<Repeated>,
+    pub(crate) completions: bool,
+    pub(crate) man: bool,
+    pub(crate) help_json: bool,
+    pub(crate) name: Option<String>,
+    pub(crate) after_help: Option<String>,
+}
+
+impl ContainerAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut out = ContainerAttrs::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("auto_args")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename_all") {
+                    out.rename_all = Some(RenameRule::from_lit(&meta.value()?.parse()?)?);
+                    Ok(())
+                } else if meta.path.is_ident("env_prefix") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    out.env_prefix = Some(lit.value());
+                    Ok(())
+                } else if meta.path.is_ident("subcommand") {
+                    out.subcommand = true;
+                    Ok(())
+                } else if meta.path.is_ident("completions") {
+                    out.completions = true;
+                    Ok(())
+                } else if meta.path.is_ident("man") {
+                    out.man = true;
+                    Ok(())
+                } else if meta.path.is_ident("help_json") {
+                    out.help_json = true;
+                    Ok(())
+                } else if meta.path.is_ident("name") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    out.name = Some(lit.value());
+                    Ok(())
+                } else if meta.path.is_ident("after_help") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    out.after_help = Some(lit.value());
+                    Ok(())
+                } else if meta.path.is_ident("repeated") {
+                    out.repeated = Some(Repeated::from_lit(&meta.value()?.parse()?)?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported auto_args attribute on a type"))
+                }
+            })?;
+        }
+        Ok(out)
+    }
+
+    fn rule(&self) -> RenameRule {
+        self.rename_all.unwrap_or(RenameRule::KebabCase)
+    }
+}
+
+/// Attributes given on a single field or enum variant.
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    pub(crate) rename: Option<String>,
+    pub(crate) short: Option<char>,
+    /// The default value, with `Some(None)` meaning `Default::default()`.
+    pub(crate) default: Option<Option<Expr>>,
+    pub(crate) env: Option<String>,
+    pub(crate) positional: bool,
+    pub(crate) flatten: bool,
+    pub(crate) trailing: bool,
+    pub(crate) repeated: Option<Repeated>,
+}
+
+impl FieldAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut out = FieldAttrs::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("auto_args")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    out.rename = Some(lit.value());
+                    Ok(())
+                } else if meta.path.is_ident("short") {
+                    let lit: LitChar = meta.value()?.parse()?;
+                    if !lit.value().is_alphanumeric() {
+                        return Err(syn::Error::new(
+                            lit.span(),
+                            "a short flag must be a letter or digit",
+                        ));
+                    }
+                    out.short = Some(lit.value());
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    if meta.input.peek(Token![=]) {
+                        out.default = Some(Some(meta.value()?.parse()?));
+                    } else {
+                        out.default = Some(None);
+                    }
+                    Ok(())
+                } else if meta.path.is_ident("env") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    out.env = Some(lit.value());
+                    Ok(())
+                } else if meta.path.is_ident("positional") {
+                    out.positional = true;
+                    Ok(())
+                } else if meta.path.is_ident("flatten") {
+                    out.flatten = true;
+                    Ok(())
+                } else if meta.path.is_ident("trailing") {
+                    out.trailing = true;
+                    Ok(())
+                } else if meta.path.is_ident("repeated") {
+                    out.repeated = Some(Repeated::from_lit(&meta.value()?.parse()?)?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported auto_args attribute on a field"))
+                }
+            })?;
+        }
+        Ok(out)
+    }
+
+    /// Check that `flatten` is not combined with attributes that give
+    /// the field or variant a name or flag of its own, and that
+    /// `positional` and `trailing` fields are not given a flag or a
+    /// policy for repeats.
+    pub(crate) fn check(&self, span: &impl quote::ToTokens) -> Result<()> {
+        let msg = if self.flatten
+            && (self.rename.is_some()
+                || self.short.is_some()
+                || self.positional
+                || self.trailing
+                || self.env.is_some())
+        {
+            "flatten cannot be combined with rename, short, positional, trailing or env"
+        } else if self.positional && (self.short.is_some() || self.env.is_some()) {
+            "a positional field cannot have a short flag or environment variable"
+        } else if self.trailing
+            && (self.short.is_some() || self.env.is_some() || self.positional || self.default.is_some())
+        {
+            "trailing cannot be combined with short, env, positional or default"
+        } else if self.repeated.is_some() && (self.flatten || self.positional || self.trailing) {
+            "repeated cannot be combined with flatten, positional or trailing"
+        } else {
+            return Ok(());
+        };
+        Err(syn::Error::new_spanned(span, msg))
+    }
+
+    /// The flag name for a struct field called `name`.
+    ///
+    /// A flattened field gets an empty name, which merges its flags
+    /// into its parent.  So does a leading underscore, unless the
+    /// field has been renamed.
+    pub(crate) fn field_name(&self, name: &str, container: &ContainerAttrs) -> String {
+        if self.flatten {
+            String::new()
+        } else if let Some(ref rename) = self.rename {
+            rename.clone()
+        } else if name.starts_with('_') {
+            String::new()
+        } else {
+            container.rule().apply_to_field(name)
+        }
+    }
+
+    /// The environment variable for a struct field called `name`, if any.
+    ///
+    /// Positional, trailing and flattened fields are not given one by
+    /// an `env_prefix`.
+    pub(crate) fn field_env(&self, name: &str, container: &ContainerAttrs) -> Option<String> {
+        if let Some(ref env) = self.env {
+            Some(env.clone())
+        } else if self.positional || self.trailing || self.flatten {
+            None
+        } else {
+            container
+                .env_prefix
+                .as_ref()
+                .map(|prefix| format!("{}{}", prefix, name.to_uppercase()))
+        }
+    }
+
+    /// What to do when the flag of this field is given more than once.
+    pub(crate) fn repeated(&self, container: &ContainerAttrs) -> Repeated {
+        self.repeated.or(container.repeated).unwrap_or(Repeated::Error)
+    }
+
+    /// The name shown in the usage for a positional or trailing field
+    /// called `name`.
+    pub(crate) fn positional_name(&self, name: &str) -> String {
+        match self.rename {
+            Some(ref rename) => rename.to_uppercase(),
+            None => name.trim_start_matches('_').to_uppercase(),
+        }
+    }
+
+    /// The flag name for an enum variant called `name`.
+    pub(crate) fn variant_name(&self, name: &str, container: &ContainerAttrs) -> String {
+        if self.flatten {
+            String::new()
+        } else if let Some(ref rename) = self.rename {
+            rename.clone()
+        } else if name.starts_with('_') {
+            String::new()
+        } else {
+            container.rule().apply_to_variant(name)
+        }
+    }
+}
@@ -17,71 +17,463 @@ extern crate syn;
 extern crate quote;
 extern crate proc_macro2;
 
+mod attrs;
+
+use attrs::{ContainerAttrs, FieldAttrs, Repeated};
 use syn::*;
 
-fn get_doc_comment(attrs: &[syn::Attribute]) -> String {
-    let mut doc_comments = attrs
+/// An expression giving the raw doc comment, with its `doc`
+/// attributes joined by newlines.  These may be macros such as
+/// `include_str!`, so the text is only tidied up at runtime, by
+/// `auto_args::doc_text` and its friends.
+fn get_doc_comment(attrs: &[syn::Attribute]) -> proc_macro2::TokenStream {
+    let values: Vec<&Expr> = attrs
         .iter()
-        .filter_map(|attr| {
-            let path = attr.path();
-            if quote!(#path).to_string() == "doc" {
-                Some(attr.meta.clone())
-            } else {
-                None
-            }
+        .filter_map(|attr| match attr.meta {
+            Meta::NameValue(MetaNameValue {
+                ref path,
+                ref value,
+                ..
+            }) if path.is_ident("doc") => Some(value),
+            _ => None,
         })
-        .filter_map(|attr| {
-            use Lit::*;
-            use Meta::*;
-            if let NameValue(MetaNameValue {
-                path, value: Expr::Lit(ExprLit { lit: Str(s), .. }), ..
-            }) = attr
-            {
-                if !path.is_ident("doc") {
-                    return None;
-                }
-                let value = s.value();
-                let text = value
-                    .trim_start_matches("//!")
-                    .trim_start_matches("///")
-                    .trim_start_matches("/*!")
-                    .trim_start_matches("/**")
-                    .trim_end_matches("*/")
-                    .trim();
-                if text.is_empty() {
-                    Some("\n\n".to_string())
-                } else {
-                    Some(text.to_string())
+        .collect();
+    if values.is_empty() {
+        return quote!("");
+    }
+    let mut parts = Vec::new();
+    for (i, value) in values.into_iter().enumerate() {
+        if i > 0 {
+            parts.push(quote!("\n"));
+        }
+        parts.push(quote!(#value));
+    }
+    quote!(concat!( #( #parts ),* ))
+}
+
+/// A named field, along with the flag name it is given.
+///
+/// For a positional or trailing field the name is instead the one
+/// shown in the usage, such as `INPUT`.
+struct NamedField {
+    ident: syn::Ident,
+    ty: syn::Type,
+    name: String,
+    doc: proc_macro2::TokenStream,
+    short: Option<char>,
+    default: Option<Option<Expr>>,
+    env: Option<String>,
+    positional: bool,
+    trailing: bool,
+    repeated: Repeated,
+}
+
+impl NamedField {
+    /// Whether this field is given by a flag, rather than being
+    /// positional or trailing.
+    fn is_flag(&self) -> bool {
+        !self.positional && !self.trailing
+    }
+
+    /// An expression giving the short flag for this field, if any.
+    ///
+    /// Besides an explicit `short`, a field whose whole flag is a
+    /// single character (such as `--N`) gets that character as its
+    /// short flag.  Whether the flag is that short depends on the
+    /// prefix, which we only know at runtime, so the expression
+    /// refers to the full flag as `flag`.
+    fn short(&self) -> proc_macro2::TokenStream {
+        let mut chars = self.name.chars();
+        match (self.short, chars.next(), chars.next()) {
+            (Some(c), _, _) => quote!(Some(#c)),
+            (None, Some(c), None) => quote! {
+                if flag.len() == 2 + #c.len_utf8() { Some(#c) } else { None }
+            },
+            _ => quote!(None),
+        }
+    }
+
+    /// An expression that is true if this field requires input.
+    fn requires_input(&self) -> proc_macro2::TokenStream {
+        let ty = &self.ty;
+        if self.default.is_some() || self.trailing {
+            quote!(false)
+        } else {
+            quote!(<#ty as auto_args::AutoArgs>::REQUIRES_INPUT)
+        }
+    }
+
+    /// An expression parsing this field from `args`, using the flag
+    /// given by `join_prefix`.
+    fn parse(&self) -> proc_macro2::TokenStream {
+        let ty = &self.ty;
+        let name = &self.name;
+        let parse = match self.env {
+            None if self.trailing => quote! {
+                Ok::<_, auto_args::Error>(auto_args::parse_trailing(args))
+            },
+            None if self.positional => quote! {
+                auto_args::parse_positional::<#ty>(#name, args)
+            },
+            None => quote! {
+                <#ty as auto_args::AutoArgs>::parse_internal(&join_prefix(#name), args)
+            },
+            Some(ref env) => quote! {
+                auto_args::parse_with_env::<#ty>(&join_prefix(#name), #env, args)
+            },
+        };
+        let parse = if self.repeated == Repeated::Last && self.is_flag() {
+            quote! {
+                auto_args::parse_last::<#ty>(&join_prefix(#name), args, |args| #parse)
+            }
+        } else {
+            parse
+        };
+        match self.default {
+            None => parse,
+            Some(ref default) => {
+                // A type such as `Vec` is parsed even if its flag is
+                // not given, so we also use the default if nothing was
+                // taken from the arguments or environment.
+                let from_env = match self.env {
+                    Some(ref env) => quote!(std::env::var_os(#env).is_some()),
+                    None => quote!(false),
+                };
+                let default = match default {
+                    None => quote!(Default::default()),
+                    // A string literal is presumably meant for a
+                    // `String` or `PathBuf` field.
+                    Some(Expr::Lit(ExprLit { lit: Lit::Str(s), .. })) => {
+                        quote!(std::convert::Into::into(#s))
+                    }
+                    Some(e) => quote!(#e),
+                };
+                quote! {
+                    {
+                        let () = auto_args::TakesValue::<#ty>::CHECK;
+                        let given = args.len();
+                        match #parse {
+                            Err(auto_args::Error::MissingOption { .. })
+                            | Err(auto_args::Error::MissingArgument { .. }) => Ok(#default),
+                            Ok(_) if args.len() == given && !#from_env => Ok(#default),
+                            r => r,
+                        }
+                    }
                 }
-            } else {
-                None
             }
-        });
+        }
+    }
+
+    /// An expression giving the `ArgSpec` of this field, using the
+    /// flag given by `join_prefix`.
+    fn spec(&self) -> proc_macro2::TokenStream {
+        let ty = &self.ty;
+        let name = &self.name;
+        let doc = &self.doc;
+        let optional = self.default.is_some() || self.env.is_some();
+        if self.trailing {
+            quote! {
+                {
+                    let mut spec = auto_args::ArgSpec::value(#name, #name);
+                    spec.doc = auto_args::doc_text(#doc);
+                    spec.optional = true;
+                    spec.repeatable = true;
+                    spec.positional = true;
+                    spec
+                }
+            }
+        } else if self.positional {
+            quote! {
+                {
+                    let mut spec = <#ty as auto_args::AutoArgs>::spec("");
+                    spec.name = #name.to_string();
+                    spec.doc = auto_args::doc_text(#doc);
+                    spec.optional |= #optional;
+                    spec.positional = true;
+                    spec
+                }
+            }
+        } else {
+            let short = self.short();
+            quote! {
+                {
+                    let flag = join_prefix(#name);
+                    let mut spec = <#ty as auto_args::AutoArgs>::spec(&flag);
+                    spec.short = #short;
+                    spec.doc = auto_args::doc_text(#doc);
+                    spec.optional |= #optional;
+                    spec
+                }
+            }
+        }
+    }
+
+    /// Like `parse`, but giving every error found in a `Vec`.
+    fn parse_all(&self) -> proc_macro2::TokenStream {
+        let ty = &self.ty;
+        let name = &self.name;
+        if self.is_flag()
+            && self.env.is_none()
+            && self.default.is_none()
+            && self.repeated == Repeated::Error
+        {
+            quote! {
+                <#ty as auto_args::AutoArgs>::parse_internal_all(&join_prefix(#name), args)
+            }
+        } else {
+            let parse = self.parse();
+            quote!((#parse).map_err(|e| vec![e]))
+        }
+    }
+
+    /// An expression for the help for this field, including its
+    /// default value and environment variable.
+    fn doc(&self) -> proc_macro2::TokenStream {
+        let doc = &self.doc;
+        let default = match self.default {
+            Some(Some(Expr::Lit(ExprLit {
+                lit: Lit::Str(ref s),
+                ..
+            }))) => Some(s.value()),
+            Some(Some(ref e)) => Some(tokens_text(quote!(#e))),
+            _ => None,
+        };
+        let notes = default
+            .map(|d| format!("[default: {}]", d))
+            .into_iter()
+            .chain(self.env.as_ref().map(|e| format!("[env: {}]", e)));
+        let notes = notes.collect::<Vec<_>>().join(" ");
+        quote!(auto_args::help_doc(#doc, #notes))
+    }
+}
+
+/// Whether `ty` is written as `bool`.
+fn is_bool(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(p) => p.qself.is_none() && p.path.is_ident("bool"),
+        _ => false,
+    }
+}
+
+/// Rust source for `tokens`, spaced as it would usually be written,
+/// such as `vec![1, 2]` rather than `vec ! [1 , 2]`.
+fn tokens_text(tokens: proc_macro2::TokenStream) -> String {
+    use proc_macro2::{Delimiter, TokenTree};
+    let mut out = String::new();
+    let mut word = false;
+    for tt in tokens {
+        match tt {
+            TokenTree::Group(g) => {
+                let (open, close) = match g.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Brace => ("{ ", " }"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => ("", ""),
+                };
+                out.push_str(open);
+                out.push_str(&tokens_text(g.stream()));
+                out.push_str(close);
+                word = false;
+            }
+            TokenTree::Punct(p) => {
+                out.push(p.as_char());
+                if p.as_char() == ',' || p.as_char() == ';' {
+                    out.push(' ');
+                }
+                word = false;
+            }
+            TokenTree::Ident(_) | TokenTree::Literal(_) => {
+                if word {
+                    out.push(' ');
+                }
+                out.push_str(&tt.to_string());
+                word = true;
+            }
+        }
+    }
+    out
+}
+
+/// Something that would give a flag (or a whole set of flags) to a
+/// struct or enum, used to catch flags that would be given twice.
+#[derive(PartialEq)]
+enum FlagKey {
+    /// A flag with this name.
+    Name(String),
+    /// A short flag.
+    Short(char),
+    /// The flags of a flattened field of this type.
+    Flattened(String),
+}
+
+impl FlagKey {
+    fn for_field(name: &str, ty: &syn::Type) -> FlagKey {
+        if name.is_empty() {
+            FlagKey::Flattened(quote!(#ty).to_string())
+        } else {
+            FlagKey::Name(name.to_string())
+        }
+    }
+}
 
-    doc_comments.next().unwrap_or_else(String::new)
+/// Reject two keys that are the same.  We cannot see the flags within
+/// a flattened type, so we can only catch flattening the same type
+/// twice, and `auto_args` checks the rest when parsing.
+fn check_duplicate_flags(keys: Vec<(FlagKey, proc_macro2::TokenStream)>) -> Result<()> {
+    for (i, (key, span)) in keys.iter().enumerate() {
+        if keys[..i].iter().any(|(k, _)| k == key) {
+            let msg = match key {
+                FlagKey::Name(name) => format!("the flag name `{}` is used more than once", name),
+                FlagKey::Short(c) => format!("the short flag `-{}` is used more than once", c),
+                FlagKey::Flattened(ty) => format!(
+                    "flattening `{}` more than once would give identical flags",
+                    ty
+                ),
+            };
+            return Err(Error::new_spanned(span, msg));
+        }
+    }
+    Ok(())
+}
+
+/// An expression that is true if any of the named fields requires input.
+fn requires_input(f: &[NamedField]) -> proc_macro2::TokenStream {
+    let requires = f.iter().map(|x| x.requires_input());
+    quote!(#( #requires ||)* false)
+}
+
+/// The fields of a struct or enum variant, with their attributes parsed.
+enum Fields {
+    Named(Vec<NamedField>),
+    Unit,
+    Unnamed(syn::Type),
+}
+
+impl Fields {
+    /// The flags given directly by these named fields.  A field whose
+    /// name is a single character counts as having that short flag,
+    /// although it only gets it when its flag is that short.
+    fn flag_keys(&self) -> Vec<(FlagKey, proc_macro2::TokenStream)> {
+        match self {
+            Fields::Named(ref f) => f
+                .iter()
+                .filter(|x| x.is_flag())
+                .flat_map(|x| {
+                    let ident = &x.ident;
+                    let mut chars = x.name.chars();
+                    let short = match (x.short, chars.next(), chars.next()) {
+                        (Some(c), _, _) | (None, Some(c), None) => Some(FlagKey::Short(c)),
+                        _ => None,
+                    };
+                    std::iter::once(FlagKey::for_field(&x.name, &x.ty))
+                        .chain(short)
+                        .map(move |key| (key, quote!(#ident)))
+                })
+                .collect(),
+            Fields::Unit | Fields::Unnamed(_) => Vec::new(),
+        }
+    }
+
+    /// An expression that is true if these fields require input.
+    fn requires_input(&self) -> proc_macro2::TokenStream {
+        match self {
+            Fields::Named(ref f) => requires_input(f),
+            Fields::Unit => quote!(false),
+            Fields::Unnamed(ref mytype) => quote!(<#mytype as auto_args::AutoArgs>::REQUIRES_INPUT),
+        }
+    }
+
+    fn parse(f: &syn::Fields, container: &ContainerAttrs) -> Result<Fields> {
+        match f {
+            syn::Fields::Named(ref fields) => {
+                let mut out = Vec::new();
+                for x in fields.named.iter() {
+                    let ident = x.ident.clone().unwrap();
+                    let attrs = FieldAttrs::parse(&x.attrs)?;
+                    attrs.check(x)?;
+                    // Other types that take no value are caught when
+                    // the parsing code is compiled.
+                    if attrs.default.is_some() && is_bool(&x.ty) {
+                        return Err(Error::new_spanned(
+                            x,
+                            "`default` cannot be given for a type that takes no value, such as `bool`",
+                        ));
+                    }
+                    let name = if attrs.positional || attrs.trailing {
+                        attrs.positional_name(&ident.to_string())
+                    } else {
+                        attrs.field_name(&ident.to_string(), container)
+                    };
+                    out.push(NamedField {
+                        name,
+                        ty: x.ty.clone(),
+                        doc: get_doc_comment(&x.attrs),
+                        short: attrs.short,
+                        default: attrs.default.clone(),
+                        env: attrs.field_env(&ident.to_string(), container),
+                        positional: attrs.positional,
+                        trailing: attrs.trailing,
+                        repeated: attrs.repeated(container),
+                        ident,
+                    });
+                }
+                let out = Fields::Named(out);
+                check_duplicate_flags(out.flag_keys())?;
+                Ok(out)
+            }
+            syn::Fields::Unit => Ok(Fields::Unit),
+            syn::Fields::Unnamed(ref unnamed) if unnamed.unnamed.len() == 1 => {
+                let f = unnamed
+                    .unnamed
+                    .iter()
+                    .next()
+                    .expect("we should have one field");
+                if let Some(attr) = f.attrs.iter().find(|a| a.path().is_ident("auto_args")) {
+                    return Err(Error::new_spanned(
+                        attr,
+                        "auto_args attributes are not supported on tuple fields",
+                    ));
+                }
+                Ok(Fields::Unnamed(f.ty.clone()))
+            }
+            syn::Fields::Unnamed(ref unnamed) => Err(Error::new_spanned(
+                unnamed,
+                "AutoArgs does not handle tuples with more than one field",
+            )),
+        }
+    }
 }
 
 fn return_with_fields(
-    f: syn::Fields,
+    f: &Fields,
     name: proc_macro2::TokenStream,
     am_enum_variant: bool,
 ) -> proc_macro2::TokenStream {
     let join_prefix = create_join_prefix();
     match f {
-        syn::Fields::Named(ref fields) => {
-            let f: Vec<_> = fields.named.clone().into_iter().collect();
-            let names = f
-                .iter()
-                .map(|x| snake_case_to_kebab(&x.ident.clone().unwrap().to_string()));
-            let types = f.iter().map(|x| x.ty.clone());
-            let types2 = types.clone();
-            let idents = f.iter().map(|x| x.ident.clone().unwrap());
+        Fields::Named(ref f) => {
+            // Trailing fields are parsed first, so that nothing else
+            // looks beyond the `--`, and positional fields are parsed
+            // last, so that they are not confused with the values of
+            // flags.
+            let ordered = || {
+                f.iter()
+                    .filter(|x| x.trailing)
+                    .chain(f.iter().filter(|x| x.is_flag()))
+                    .chain(f.iter().filter(|x| x.positional))
+            };
+            let parses = ordered().map(|x| x.parse());
+            let idents = ordered().map(|x| &x.ident);
+            let requires_input = requires_input(f);
             let check_main_flag = if am_enum_variant {
                 quote! {
-                    if #( <#types2 as auto_args::AutoArgs>::REQUIRES_INPUT ||)* false {
+                    if #requires_input {
                         // Nothing special to do, something below requires input.
                     } else if !bool::parse_internal(&_prefix, args)? {
-                        return Err(auto_args::Error::MissingOption(_prefix.clone()));
+                        return Err(auto_args::Error::MissingOption {
+                            flag: _prefix.clone(),
+                            expected: String::new(),
+                        });
                     }
                 }
             } else {
@@ -92,55 +484,128 @@ fn return_with_fields(
                 let join_prefix = #join_prefix;
                 // Am in return_with_fields
                 Ok( #name {
-                    #( #idents:
-                        <#types as auto_args::AutoArgs>::parse_internal(&join_prefix(#names),
-                                                                        args)?,  )*
+                    #( #idents: #parses?, )*
                 })
             }
         }
-        syn::Fields::Unit => {
+        Fields::Unit => {
             quote! {
                 if bool::parse_internal(&_prefix, args)? {
                     Ok( #name )
                 } else {
-                    Err(auto_args::Error::MissingOption(_prefix.clone()))
+                    Err(auto_args::Error::MissingOption {
+                        flag: _prefix.clone(),
+                        expected: String::new(),
+                    })
                 }
             }
         }
-        syn::Fields::Unnamed(ref unnamed) if unnamed.unnamed.len() == 1 => {
-            let f = unnamed
-                .unnamed
-                .iter()
-                .next()
-                .expect("we should have one field");
-            let mytype = f.ty.clone();
+        Fields::Unnamed(ref mytype) => {
             quote! {
                 <#mytype as auto_args::AutoArgs>::parse_internal(&_prefix, args).map(|x| #name(x))
             }
         }
-        _ => {
-            panic!("AutoArgs only supports named fields so far!")
+    }
+}
+
+/// Like `return_with_fields`, but for `parse_internal_all`, which
+/// parses every field even when some fail, so as to report all of the
+/// errors.
+fn return_all_with_fields(
+    f: &Fields,
+    name: proc_macro2::TokenStream,
+    am_enum_variant: bool,
+) -> proc_macro2::TokenStream {
+    let join_prefix = create_join_prefix();
+    match f {
+        Fields::Named(ref f) => {
+            // The same order as in `return_with_fields`.
+            let ordered: Vec<_> = f
+                .iter()
+                .filter(|x| x.trailing)
+                .chain(f.iter().filter(|x| x.is_flag()))
+                .chain(f.iter().filter(|x| x.positional))
+                .collect();
+            let parses = ordered.iter().map(|x| x.parse_all());
+            let idents: Vec<_> = ordered.iter().map(|x| &x.ident).collect();
+            let values: Vec<_> = (0..ordered.len())
+                .map(|i| format_ident!("_auto_args_field_{}", i))
+                .collect();
+            let requires_input = requires_input(f);
+            let check_main_flag = if am_enum_variant {
+                quote! {
+                    if #requires_input {
+                        // Nothing special to do, something below requires input.
+                    } else {
+                        match bool::parse_internal(&_prefix, args) {
+                            Ok(true) => (),
+                            Ok(false) => errors.push(auto_args::Error::MissingOption {
+                                flag: _prefix.clone(),
+                                expected: String::new(),
+                            }),
+                            Err(e) => errors.push(e),
+                        }
+                    }
+                }
+            } else {
+                quote! {}
+            };
+            quote! {
+                let mut errors = Vec::new();
+                #check_main_flag
+                let join_prefix = #join_prefix;
+                #(
+                    let #values = match #parses {
+                        Ok(v) => Some(v),
+                        Err(e) => {
+                            errors.extend(e);
+                            None
+                        }
+                    };
+                )*
+                if !errors.is_empty() {
+                    return Err(errors);
+                }
+                Ok( #name {
+                    #( #idents: #values.expect("there were no errors"), )*
+                })
+            }
+        }
+        Fields::Unit => {
+            let return_unit = return_with_fields(f, name, am_enum_variant);
+            quote! {
+                (|| -> Result<_, auto_args::Error> { #return_unit })().map_err(|e| vec![e])
+            }
+        }
+        Fields::Unnamed(ref mytype) => {
+            quote! {
+                <#mytype as auto_args::AutoArgs>::parse_internal_all(&_prefix, args).map(|x| #name(x))
+            }
         }
     }
 }
 
 fn usage_with_fields(
-    f: syn::Fields,
+    f: &Fields,
     _name: proc_macro2::TokenStream,
     am_enum_variant: bool,
 ) -> proc_macro2::TokenStream {
     let join_prefix = create_join_prefix();
     match f {
-        syn::Fields::Named(ref fields) => {
-            let f: Vec<_> = fields.named.clone().into_iter().collect();
-            let names = f
-                .iter()
-                .map(|x| snake_case_to_kebab(&x.ident.clone().unwrap().to_string()));
-            let types = f.iter().map(|x| x.ty.clone());
-            let types2 = types.clone();
+        Fields::Named(ref f) => {
+            let flags = || f.iter().filter(|x| x.is_flag());
+            let names = flags().map(|x| &x.name);
+            let types = flags().map(|x| &x.ty);
+            let has_default = flags().map(|x| x.default.is_some());
+            let tnames = f.iter().filter(|x| x.trailing).map(|x| &x.name);
+            let positionals = || f.iter().filter(|x| x.positional);
+            let pnames = positionals().map(|x| &x.name);
+            let ptypes = positionals().map(|x| &x.ty);
+            let phas_default = positionals().map(|x| x.default.is_some());
+            let requires_input = requires_input(f);
             let check_main_flag = if am_enum_variant {
                 quote! {
-                    if #( <#types2 as auto_args::AutoArgs>::REQUIRES_INPUT ||)* false {
+                    if #requires_input {
                         // Nothing special to do, something below requires input.
                     } else {
                         doc.push_str(&format!("{} ", _prefix));
@@ -153,51 +618,58 @@ fn usage_with_fields(
                 let mut doc = String::new();
                 #check_main_flag
                 let join_prefix = #join_prefix;
-                #( doc.push_str(
-                    &format!(" {}",
-                             <#types as auto_args::AutoArgs>::tiny_help_message(&join_prefix(#names))));
+                #( {
+                    let tiny = <#types as auto_args::AutoArgs>::tiny_help_message(&join_prefix(#names));
+                    if #has_default && !tiny.starts_with('[') {
+                        doc.push_str(&format!(" [{}]", tiny));
+                    } else {
+                        doc.push_str(&format!(" {}", tiny));
+                    }
+                } )*
+                #(
+                    doc.push_str(" ");
+                    doc.push_str(&auto_args::positional_usage::<#ptypes>(#pnames, #phas_default));
+                )*
+                #(
+                    doc.push_str(&format!(" [-- {}...]", #tnames));
                 )*
                 doc
             }
         }
-        syn::Fields::Unit => {
+        Fields::Unit => {
             quote!(_prefix.clone())
         }
-        syn::Fields::Unnamed(ref unnamed) if unnamed.unnamed.len() == 1 => {
-            let f = unnamed
-                .unnamed
-                .iter()
-                .next()
-                .expect("we should have one field");
-            let mytype = f.ty.clone();
+        Fields::Unnamed(ref mytype) => {
             quote! {
                 <#mytype as auto_args::AutoArgs>::tiny_help_message(&_prefix)
             }
         }
-        _ => {
-            panic!("AutoArgs only supports named fields so far!")
-        }
     }
 }
 
 fn help_with_fields(
-    f: syn::Fields,
+    f: &Fields,
     _name: proc_macro2::TokenStream,
     am_enum_variant: bool,
 ) -> proc_macro2::TokenStream {
     let join_prefix = create_join_prefix();
     match f {
-        syn::Fields::Named(ref fields) => {
-            let f: Vec<_> = fields.named.clone().into_iter().collect();
-            let docs: Vec<_> = f.iter().map(|x| get_doc_comment(&x.attrs)).collect();
-            let names = f
-                .iter()
-                .map(|x| snake_case_to_kebab(&x.ident.clone().unwrap().to_string()));
-            let types = f.iter().map(|x| x.ty.clone());
-            let types2 = types.clone();
+        Fields::Named(ref f) => {
+            let flags = || f.iter().filter(|x| x.is_flag());
+            let docs = flags().map(|x| x.doc());
+            let names = flags().map(|x| &x.name);
+            let shorts = flags().map(|x| x.short());
+            let types = flags().map(|x| &x.ty);
+            let positionals = || f.iter().filter(|x| x.positional);
+            let pdocs = positionals().map(|x| x.doc());
+            let pnames = positionals().map(|x| &x.name);
+            let trailings = || f.iter().filter(|x| x.trailing);
+            let tdocs = trailings().map(|x| x.doc());
+            let tnames = trailings().map(|x| &x.name);
+            let requires_input = requires_input(f);
             let check_main_flag = if am_enum_variant {
                 quote! {
-                    if #( <#types2 as auto_args::AutoArgs>::REQUIRES_INPUT ||)* false {
+                    if #requires_input {
                         // Nothing special to do, something below requires input.
                     } else {
                         doc.push_str(&format!("\t{}\t{}\n", _prefix, variant_doc));
@@ -210,32 +682,301 @@ fn help_with_fields(
                 let mut doc = String::new();
                 #check_main_flag
                 let join_prefix = #join_prefix;
-                #( doc.push_str(
-                    &<#types as auto_args::AutoArgs>::help_message(&join_prefix(#names),
-                                                                   #docs));
+                #( {
+                    let flag = join_prefix(#names);
+                    let short: Option<char> = #shorts;
+                    let help = <#types as auto_args::AutoArgs>::help_message(&flag, &#docs);
+                    if let Some(c) = short {
+                        doc.push_str(&help.replacen('\t', &format!("\t-{}, ", c), 1));
+                    } else {
+                        doc.push_str(&help);
+                    }
+                   }
                    if !doc.ends_with("\n") {
                        doc.push('\n');
                    }
                 )*
+                #(
+                    doc.push_str(&format!("\t{}\t{}\n", #pnames, #pdocs));
+                )*
+                #(
+                    doc.push_str(&format!("\t[-- {}...]\t{}\n", #tnames, #tdocs));
+                )*
                 doc
             }
         }
-        syn::Fields::Unit => {
+        Fields::Unit => {
             quote!(format!("\t{}\t{}\n", _prefix, variant_doc))
         }
-        syn::Fields::Unnamed(ref unnamed) if unnamed.unnamed.len() == 1 => {
-            let f = unnamed
-                .unnamed
-                .iter()
-                .next()
-                .expect("we should have one field");
-            let mytype = f.ty.clone();
+        Fields::Unnamed(ref mytype) => {
             quote! {
                 <#mytype as auto_args::AutoArgs>::help_message(&_prefix, &variant_doc)
             }
         }
-        _ => {
-            panic!("AutoArgs only supports named fields so far!")
+    }
+}
+
+fn shorts_with_fields(f: &Fields) -> proc_macro2::TokenStream {
+    let join_prefix = create_join_prefix();
+    match f {
+        Fields::Named(ref f) => {
+            let flags = || f.iter().filter(|x| x.is_flag());
+            let names = flags().map(|x| &x.name);
+            let shorts = flags().map(|x| x.short());
+            let types = flags().map(|x| &x.ty);
+            quote! {
+                let mut shorts = Vec::new();
+                let join_prefix = #join_prefix;
+                #(
+                    let flag = join_prefix(#names);
+                    shorts.extend(<#types as auto_args::AutoArgs>::short_flags(&flag));
+                    let short: Option<char> = #shorts;
+                    if let Some(c) = short {
+                        shorts.push((c, flag, <#types as auto_args::AutoArgs>::TAKES_VALUE));
+                    }
+                )*
+                shorts
+            }
+        }
+        Fields::Unit => {
+            quote!(Vec::new())
+        }
+        Fields::Unnamed(ref mytype) => {
+            quote! {
+                <#mytype as auto_args::AutoArgs>::short_flags(&_prefix)
+            }
+        }
+    }
+}
+
+fn flags_with_fields(f: &Fields, am_enum_variant: bool) -> proc_macro2::TokenStream {
+    let join_prefix = create_join_prefix();
+    match f {
+        Fields::Named(ref f) => {
+            let flags = || f.iter().filter(|x| x.is_flag());
+            let names = flags().map(|x| &x.name);
+            let types = flags().map(|x| &x.ty);
+            let requires_input = requires_input(f);
+            quote! {
+                let mut flags = Vec::new();
+                if #am_enum_variant && !(#requires_input) {
+                    flags.push(_prefix.clone());
+                }
+                let join_prefix = #join_prefix;
+                #(
+                    flags.extend(<#types as auto_args::AutoArgs>::flag_names(&join_prefix(#names)));
+                )*
+                flags
+            }
+        }
+        Fields::Unit => {
+            quote!(vec![_prefix.clone()])
+        }
+        Fields::Unnamed(ref mytype) => {
+            quote! {
+                <#mytype as auto_args::AutoArgs>::flag_names(&_prefix)
+            }
+        }
+    }
+}
+
+/// An expression giving the `ArgSpec` of these fields, named by
+/// `_prefix`.
+fn spec_with_fields(f: &Fields) -> proc_macro2::TokenStream {
+    let join_prefix = create_join_prefix();
+    match f {
+        Fields::Named(ref f) => {
+            let specs = f.iter().map(|x| x.spec());
+            let requires_input = requires_input(f);
+            quote! {
+                {
+                    let group = _prefix.clone();
+                    let join_prefix = #join_prefix;
+                    let mut spec = auto_args::ArgSpec::new(
+                        group,
+                        auto_args::ArgKind::Group(vec![ #( #specs ),* ]),
+                    );
+                    spec.optional = !(#requires_input);
+                    spec
+                }
+            }
+        }
+        Fields::Unit => {
+            quote!(auto_args::ArgSpec::new(_prefix.clone(), auto_args::ArgKind::Switch))
+        }
+        Fields::Unnamed(ref mytype) => {
+            quote!(<#mytype as auto_args::AutoArgs>::spec(&_prefix))
+        }
+    }
+}
+
+fn subcommands_with_fields(f: &Fields) -> proc_macro2::TokenStream {
+    match f {
+        Fields::Named(ref f) => {
+            let types = f.iter().filter(|x| !x.trailing).map(|x| &x.ty);
+            quote! {
+                let mut commands = Vec::new();
+                #(
+                    commands.extend(<#types as auto_args::AutoArgs>::subcommands());
+                )*
+                commands
+            }
+        }
+        Fields::Unit => {
+            quote!(Vec::new())
+        }
+        Fields::Unnamed(ref mytype) => {
+            quote! {
+                <#mytype as auto_args::AutoArgs>::subcommands()
+            }
+        }
+    }
+}
+
+/// The body of the `AutoArgs` impl for an enum with
+/// `#[auto_args(subcommand)]`, whose variant is selected by the first
+/// argument that names one of its commands.  The fields of the variant
+/// are then parsed from the arguments that follow, without any prefix,
+/// and any arguments before the command are left to the positional
+/// fields of the enclosing type.  If no argument names a command, the
+/// first that is not a flag is reported as an unknown command.
+///
+/// The values of flags have already been joined to them as in
+/// `--config=foo`, so options of the enclosing type may come before
+/// the command, whichever order the fields are in.
+fn subcommand_impl(
+    name: &syn::Ident,
+    v: &[(&syn::Ident, Fields)],
+    vnames: &[String],
+    variant_docs: &[proc_macro2::TokenStream],
+    doc: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let parses = v.iter().map(|(variant_name, f)| match f {
+        Fields::Named(_) => return_with_fields(f, quote!(#name::#variant_name), false),
+        Fields::Unit => quote!(Ok(#name::#variant_name)),
+        Fields::Unnamed(ref mytype) => quote! {
+            <#mytype as auto_args::AutoArgs>::parse_internal("", args).map(#name::#variant_name)
+        },
+    });
+    let helps = v.iter().map(|(variant_name, f)| match f {
+        Fields::Named(_) => help_with_fields(f, quote!(#name::#variant_name), false),
+        Fields::Unit => quote!(String::new()),
+        Fields::Unnamed(ref mytype) => quote! {
+            <#mytype as auto_args::AutoArgs>::help_message("", "")
+        },
+    });
+    let shorts = v.iter().map(|(_, f)| shorts_with_fields(f));
+    let flags = v.iter().map(|(_, f)| match f {
+        Fields::Unit => quote!(Vec::new()),
+        _ => flags_with_fields(f, false),
+    });
+    let specs = v.iter().map(|(_, f)| match f {
+        Fields::Named(_) => spec_with_fields(f),
+        Fields::Unit => quote!(auto_args::ArgSpec::new("", auto_args::ArgKind::Group(Vec::new()))),
+        Fields::Unnamed(ref mytype) => quote!(<#mytype as auto_args::AutoArgs>::spec("")),
+    });
+    quote! {
+        const REQUIRES_INPUT: bool = true;
+        fn parse_internal(key: &str, args: &mut Vec<std::ffi::OsString>)
+                          -> Result<Self, auto_args::Error>
+        {
+            let end = args.iter().position(|v| v == "--").unwrap_or(args.len());
+            let commands: &[&str] = &[#(#vnames),*];
+            let command = args[..end].iter().position(|v| commands.iter().any(|c| v == c));
+            let i = match command.or_else(|| {
+                args[..end].iter().position(|v| !v.to_string_lossy().starts_with('-'))
+            }) {
+                Some(i) => i,
+                None => return Err(auto_args::Error::MissingArgument {
+                    name: "COMMAND".to_string(),
+                }),
+            };
+            let mut rest = args.split_off(i + 1);
+            let command = args.pop().expect("the command should be there");
+            let result = {
+                let args = &mut rest;
+                let _prefix = "--".to_string();
+                #(
+                    if command == #vnames {
+                        let mut closure = || -> Result<Self, auto_args::Error> {
+                            #parses
+                        };
+                        closure()
+                    } else
+                )*
+                {
+                    Err(auto_args::Error::UnexpectedArgument {
+                        value: command.to_string_lossy().into_owned(),
+                        index: None,
+                    })
+                }
+            };
+            args.append(&mut rest);
+            result
+        }
+        fn help_message(key: &str, _doc: &str) -> String {
+            let mut doc = String::new();
+            #(
+                {
+                    let _prefix = "--".to_string();
+                    let help: String = { #helps };
+                    for line in help.lines() {
+                        match line.strip_prefix('\t') {
+                            Some(line) => doc.push_str(&format!("\t{} {}\n", #vnames, line)),
+                            None if !line.is_empty() => {
+                                doc.push_str(line);
+                                doc.push('\n');
+                            }
+                            None => (),
+                        }
+                    }
+                }
+            )*
+            doc
+        }
+        fn tiny_help_message(key: &str) -> String {
+            "COMMAND".to_string()
+        }
+        fn short_flags(key: &str) -> Vec<(char, String, bool)> {
+            let mut shorts = Vec::new();
+            #(
+                {
+                    let _prefix = "--".to_string();
+                    shorts.extend({ #shorts });
+                }
+            )*
+            shorts
+        }
+        fn flag_names(key: &str) -> Vec<String> {
+            let mut flags = Vec::new();
+            #(
+                {
+                    let _prefix = "--".to_string();
+                    flags.extend({ #flags });
+                }
+            )*
+            flags
+        }
+        fn subcommands() -> Vec<(String, String)> {
+            vec![ #( (#vnames.to_string(), auto_args::doc_summary(#variant_docs)), )* ]
+        }
+        fn spec(key: &str) -> auto_args::ArgSpec {
+            let commands = vec![
+                #(
+                    {
+                        let _prefix = "--".to_string();
+                        let mut spec = #specs;
+                        spec.name = #vnames.to_string();
+                        spec.doc = auto_args::doc_text(#variant_docs);
+                        spec.positional = true;
+                        spec
+                    },
+                )*
+            ];
+            let mut spec = auto_args::ArgSpec::new("COMMAND", auto_args::ArgKind::Alternatives(commands));
+            spec.doc = auto_args::doc_text(#doc);
+            spec.positional = true;
+            spec
         }
     }
 }
@@ -268,35 +1009,55 @@ fn create_find_prefix() -> proc_macro2::TokenStream {
 }
 
 /// Generates the `AutoArgs` impl.
-#[proc_macro_derive(AutoArgs)]
+#[proc_macro_derive(AutoArgs, attributes(auto_args))]
 pub fn auto_args(raw_input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input: DeriveInput = syn::parse(raw_input).unwrap();
+    match derive_auto_args(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn derive_auto_args(input: &DeriveInput) -> Result<proc_macro2::TokenStream> {
     use syn::Data::*;
-    let input: DeriveInput = syn::parse(raw_input.clone()).unwrap();
+    let container = ContainerAttrs::parse(&input.attrs)?;
+    if container.subcommand && !matches!(input.data, Data::Enum(_)) {
+        return Err(Error::new_spanned(
+            input,
+            "subcommand is only supported on enums",
+        ));
+    }
 
     let name = &input.ident;
-    let generics = &input.generics;
+    let doc = get_doc_comment(&input.attrs);
     let find_prefix = create_find_prefix();
     let myimpl = match input.data {
         Struct(DataStruct {
-            fields: syn::Fields::Named(ref fields),
+            fields: ref f @ syn::Fields::Named(_),
             ..
         }) => {
-            let f: Vec<_> = fields.named.clone().into_iter().collect();
-            let types3 = f.iter().rev().map(|x| x.ty.clone());
-            let return_struct =
-                return_with_fields(syn::Fields::Named(fields.clone()), quote!(#name), false);
-            let usage_struct =
-                usage_with_fields(syn::Fields::Named(fields.clone()), quote!(#name), false);
-            let help_struct =
-                help_with_fields(syn::Fields::Named(fields.clone()), quote!(#name), false);
+            let f = Fields::parse(f, &container)?;
+            let requires_input = f.requires_input();
+            let return_struct = return_with_fields(&f, quote!(#name), false);
+            let return_all_struct = return_all_with_fields(&f, quote!(#name), false);
+            let usage_struct = usage_with_fields(&f, quote!(#name), false);
+            let help_struct = help_with_fields(&f, quote!(#name), false);
+            let shorts_struct = shorts_with_fields(&f);
+            let spec_struct = spec_with_fields(&f);
+            let flags_struct = flags_with_fields(&f, false);
+            let subcommands_struct = subcommands_with_fields(&f);
             quote! {
-                const REQUIRES_INPUT: bool = #(
-                    <#types3 as auto_args::AutoArgs>::REQUIRES_INPUT ||)* false;
+                const REQUIRES_INPUT: bool = #requires_input;
                 fn parse_internal(key: &str, args: &mut Vec<std::ffi::OsString>)
                                   -> Result<Self, auto_args::Error> {
                     let _prefix = #find_prefix;
                     #return_struct
                 }
+                fn parse_internal_all(key: &str, args: &mut Vec<std::ffi::OsString>)
+                                      -> Result<Self, Vec<auto_args::Error>> {
+                    let _prefix = #find_prefix;
+                    #return_all_struct
+                }
                 fn tiny_help_message(key: &str) -> String {
                     let _prefix = #find_prefix;
                     #usage_struct
@@ -305,6 +1066,24 @@ pub fn auto_args(raw_input: proc_macro::TokenStream) -> proc_macro::TokenStream
                     let _prefix = #find_prefix;
                     #help_struct
                 }
+                fn short_flags(key: &str) -> Vec<(char, String, bool)> {
+                    let _prefix = #find_prefix;
+                    #shorts_struct
+                }
+                fn flag_names(key: &str) -> Vec<String> {
+                    let _prefix = #find_prefix;
+                    #flags_struct
+                }
+                fn subcommands() -> Vec<(String, String)> {
+                    #subcommands_struct
+                }
+                fn spec(key: &str) -> auto_args::ArgSpec {
+                    let _prefix = #find_prefix;
+                    let mut spec = #spec_struct;
+                    spec.name = key.to_string();
+                    spec.doc = auto_args::doc_text(#doc);
+                    spec
+                }
             }
         }
         Struct(DataStruct {
@@ -320,6 +1099,15 @@ pub fn auto_args(raw_input: proc_macro::TokenStream) -> proc_macro::TokenStream
                 fn tiny_help_message(key: &str) -> String {
                     "".to_string()
                 }
+                fn flag_names(key: &str) -> Vec<String> {
+                    Vec::new()
+                }
+                fn spec(key: &str) -> auto_args::ArgSpec {
+                    let mut spec = auto_args::ArgSpec::new(key, auto_args::ArgKind::Group(Vec::new()));
+                    spec.doc = auto_args::doc_text(#doc);
+                    spec.optional = true;
+                    spec
+                }
             }
         }
         Struct(DataStruct {
@@ -327,7 +1115,10 @@ pub fn auto_args(raw_input: proc_macro::TokenStream) -> proc_macro::TokenStream
             ..
         }) => {
             if unnamed.unnamed.len() != 1 {
-                panic!("AutoArgs does not handle tuple structs with more than one field");
+                return Err(Error::new_spanned(
+                    unnamed,
+                    "AutoArgs does not handle tuple structs with more than one field",
+                ));
             }
             let f = unnamed
                 .unnamed
@@ -338,42 +1129,139 @@ pub fn auto_args(raw_input: proc_macro::TokenStream) -> proc_macro::TokenStream
             quote! {
                 const REQUIRES_INPUT: bool =
                     <#mytype as auto_args::AutoArgs>::REQUIRES_INPUT;
+                const TAKES_VALUE: bool =
+                    <#mytype as auto_args::AutoArgs>::TAKES_VALUE;
                 fn parse_internal(key: &str, args: &mut Vec<std::ffi::OsString>)
                                   -> Result<Self, auto_args::Error> {
                     <#mytype as auto_args::AutoArgs>::parse_internal(key, args)
                         .map(|x| #name(x))
                 }
+                fn parse_internal_all(key: &str, args: &mut Vec<std::ffi::OsString>)
+                                      -> Result<Self, Vec<auto_args::Error>> {
+                    <#mytype as auto_args::AutoArgs>::parse_internal_all(key, args)
+                        .map(|x| #name(x))
+                }
+                fn short_flags(key: &str) -> Vec<(char, String, bool)> {
+                    <#mytype as auto_args::AutoArgs>::short_flags(key)
+                }
+                fn flag_names(key: &str) -> Vec<String> {
+                    <#mytype as auto_args::AutoArgs>::flag_names(key)
+                }
+                fn subcommands() -> Vec<(String, String)> {
+                    <#mytype as auto_args::AutoArgs>::subcommands()
+                }
+                fn spec(key: &str) -> auto_args::ArgSpec {
+                    <#mytype as auto_args::AutoArgs>::spec(key)
+                }
                 fn tiny_help_message(key: &str) -> String {
                     "fixme unnamed".to_string()
                 }
             }
         }
         Enum(ref e) => {
-            let v: Vec<_> = e.variants.iter().collect();
-            let vnames: Vec<_> = e
-                .variants
-                .iter()
-                .map(|v| camel_case_to_kebab(&v.ident.to_string()))
-                .collect();
+            let mut v = Vec::new();
+            let mut vnames = Vec::new();
+            let mut keys = Vec::new();
+            for variant in e.variants.iter() {
+                let attrs = FieldAttrs::parse(&variant.attrs)?;
+                if attrs.short.is_some() {
+                    return Err(Error::new_spanned(
+                        variant,
+                        "short flags are only supported on struct fields",
+                    ));
+                }
+                if attrs.positional {
+                    return Err(Error::new_spanned(
+                        variant,
+                        "positional is only supported on struct fields",
+                    ));
+                }
+                attrs.check(variant)?;
+                if attrs.trailing {
+                    return Err(Error::new_spanned(
+                        variant,
+                        "trailing is only supported on struct fields",
+                    ));
+                }
+                let fields = Fields::parse(&variant.fields, &container)?;
+                let vname = attrs.variant_name(&variant.ident.to_string(), &container);
+                if attrs.flatten {
+                    if let Fields::Unit = fields {
+                        return Err(Error::new_spanned(
+                            variant,
+                            "a unit variant cannot be flattened",
+                        ));
+                    }
+                }
+                if vname.is_empty() {
+                    if container.subcommand {
+                        return Err(Error::new_spanned(
+                            variant,
+                            "a subcommand variant cannot be flattened",
+                        ));
+                    }
+                } else {
+                    let ident = &variant.ident;
+                    keys.push((FlagKey::Name(vname.clone()), quote!(#ident)));
+                }
+                vnames.push(vname);
+                v.push((&variant.ident, fields));
+            }
+            check_duplicate_flags(keys)?;
             let variant_docs: Vec<_> = e
                 .variants
                 .iter()
                 .map(|v| get_doc_comment(&v.attrs))
                 .collect();
+            if container.subcommand {
+                return Ok(impl_auto_args(
+                    input,
+                    &container,
+                    subcommand_impl(name, &v, &vnames, &variant_docs, &doc),
+                ));
+            }
             let vnames = &vnames;
-            // println!("variant names are {:?}", names);
-            let return_enum = v.iter().map(|v| {
-                let variant_name = v.ident.clone();
-                return_with_fields(v.fields.clone(), quote!(#name::#variant_name), true)
+            let indices: Vec<_> = (0..v.len()).collect();
+            let indices = &indices;
+            let return_enum = v.iter().map(|(variant_name, f)| {
+                return_with_fields(f, quote!(#name::#variant_name), true)
             });
-            let helps = v.iter().map(|v| {
-                let variant_name = v.ident.clone();
-                help_with_fields(v.fields.clone(), quote!(#name::#variant_name), true)
+            let helps = v.iter().map(|(variant_name, f)| {
+                help_with_fields(f, quote!(#name::#variant_name), true)
             });
-            let usages = v.iter().map(|v| {
-                let variant_name = v.ident.clone();
-                usage_with_fields(v.fields.clone(), quote!(#name::#variant_name), true)
+            let usages = v.iter().map(|(variant_name, f)| {
+                usage_with_fields(f, quote!(#name::#variant_name), true)
             });
+            let shorts = v.iter().map(|(_, f)| shorts_with_fields(f));
+            let return_all_enum: Vec<_> = v
+                .iter()
+                .map(|(variant_name, f)| {
+                    return_all_with_fields(f, quote!(#name::#variant_name), true)
+                })
+                .collect();
+            let flags: Vec<_> = v.iter().map(|(_, f)| flags_with_fields(f, true)).collect();
+            let specs = v.iter().map(|(_, f)| spec_with_fields(f));
+            // The variant is chosen by which variant's flags are given.
+            // If none are, we take the first variant that can be parsed
+            // without any of its flags.
+            let choose_variant = quote! {
+                let variant_flags: Vec<Vec<String>> = vec![
+                    #(
+                        {
+                            let variant = #vnames;
+                            let _prefix = format!("{}{}", _prefix, variant);
+                            #flags
+                        },
+                    )*
+                ];
+                let chosen = auto_args::choose_variant(args, &variant_flags);
+            };
+            let missing_variant = quote! {
+                auto_args::Error::MissingOption {
+                    flag: String::new(),
+                    expected: Self::tiny_help_message(key),
+                }
+            };
             let s = quote! {
                 const REQUIRES_INPUT: bool = true;
                 fn parse_internal(key: &str, args: &mut Vec<std::ffi::OsString>)
@@ -386,39 +1274,62 @@ pub fn auto_args(raw_input: proc_macro::TokenStream) -> proc_macro::TokenStream
                             _ => format!("{}-", key),
                         }
                     };
-                    let orig_args = args;
-                    let mut error_args = orig_args.clone();
-                    let mut most_used = 0;
-                    let mut best_err = auto_args::Error::MissingOption("a missing thingy".to_string());
+                    #choose_variant
+                    let chosen = chosen?;
                     #(
-                        {
-                            let mut args = orig_args.clone();
-                            let args = &mut args;
-                            let variant = #vnames;
-                            let _prefix = format!("{}{}", _prefix, variant);
-                            let mut closure = || -> Result<_, auto_args::Error> {
-                                #return_enum
+                        if chosen.is_none() || chosen == Some(#indices) {
+                            let mut variant_args = args.clone();
+                            let result = {
+                                let args = &mut variant_args;
+                                let variant = #vnames;
+                                let _prefix = format!("{}{}", _prefix, variant);
+                                let mut closure = || -> Result<_, auto_args::Error> {
+                                    #return_enum
+                                };
+                                closure()
                             };
-                            match closure() {
-                                Ok(v) => {
-                                    *orig_args = args.clone();
-                                    return Ok(v);
-                                }
-                                Err(e) => {
-                                    let args_used = orig_args.len() - args.len();
-                                    println!("{:?} used {} args", e, args_used);
-                                    if args_used >= most_used {
-                                        most_used = args_used;
-                                        best_err = e;
-                                        error_args = args.clone();
-                                    }
-                                }
+                            if result.is_ok() || chosen.is_some() {
+                                *args = variant_args;
+                                return result;
+                            }
+                        }
+                    )*
+                    Err(#missing_variant)
+                }
+                fn parse_internal_all(key: &str, args: &mut Vec<std::ffi::OsString>)
+                                      -> Result<Self, Vec<auto_args::Error>>
+                {
+                    let _prefix = match key.chars().next() {
+                        None | Some('_') => "--".to_string(),
+                        _ => match key.chars().last() {
+                            Some('-') => key.to_string(),
+                            _ => format!("{}-", key),
+                        }
+                    };
+                    #choose_variant
+                    let chosen = chosen.map_err(|e| {
+                        auto_args::remove_variant_flags(args, &variant_flags);
+                        vec![e]
+                    })?;
+                    #(
+                        if chosen.is_none() || chosen == Some(#indices) {
+                            let mut variant_args = args.clone();
+                            let result = {
+                                let args = &mut variant_args;
+                                let variant = #vnames;
+                                let _prefix = format!("{}{}", _prefix, variant);
+                                let mut closure = || -> Result<_, Vec<auto_args::Error>> {
+                                    #return_all_enum
+                                };
+                                closure()
+                            };
+                            if result.is_ok() || chosen.is_some() {
+                                *args = variant_args;
+                                return result;
                             }
                         }
-
                     )*
-                    *orig_args = error_args;
-                    Err(best_err)
+                    Err(vec![#missing_variant])
                 }
                 fn help_message(key: &str, doc: &str) -> String {
                     let _prefix = match key.chars().next() {
@@ -434,7 +1345,7 @@ pub fn auto_args(raw_input: proc_macro::TokenStream) -> proc_macro::TokenStream
                         {
                             let variant = #vnames;
                             let _prefix = format!("{}{}", _prefix, variant);
-                            let variant_doc = #variant_docs;
+                            let variant_doc = auto_args::help_doc(#variant_docs, "");
                             doc.push_str(&{ #helps });
                             if !doc.ends_with("\n") {
                                 doc.push_str("\n");
@@ -446,7 +1357,7 @@ pub fn auto_args(raw_input: proc_macro::TokenStream) -> proc_macro::TokenStream
                     for _ in 0.."\tOR\t\n".len() {
                         doc.pop();
                     }
-                    doc.push_str("\t\t");
+                    doc.push('\n');
                     doc
                 }
                 fn tiny_help_message(key: &str) -> String {
@@ -474,12 +1385,110 @@ pub fn auto_args(raw_input: proc_macro::TokenStream) -> proc_macro::TokenStream
                     doc.push_str(" )");
                     doc
                 }
+                fn short_flags(key: &str) -> Vec<(char, String, bool)> {
+                    let _prefix = match key.chars().next() {
+                        None | Some('_') => "--".to_string(),
+                        _ => match key.chars().last() {
+                            Some('-') => key.to_string(),
+                            _ => format!("{}-", key),
+                        }
+                    };
+                    let mut shorts = Vec::new();
+                    #(
+                        {
+                            let variant = #vnames;
+                            let _prefix = format!("{}{}", _prefix, variant);
+                            shorts.extend({ #shorts });
+                        }
+                    )*
+                    shorts
+                }
+                fn flag_names(key: &str) -> Vec<String> {
+                    let _prefix = match key.chars().next() {
+                        None | Some('_') => "--".to_string(),
+                        _ => match key.chars().last() {
+                            Some('-') => key.to_string(),
+                            _ => format!("{}-", key),
+                        }
+                    };
+                    let mut flags = Vec::new();
+                    #(
+                        {
+                            let variant = #vnames;
+                            let _prefix = format!("{}{}", _prefix, variant);
+                            flags.extend({ #flags });
+                        }
+                    )*
+                    flags
+                }
+                fn spec(key: &str) -> auto_args::ArgSpec {
+                    let _prefix = match key.chars().next() {
+                        None | Some('_') => "--".to_string(),
+                        _ => match key.chars().last() {
+                            Some('-') => key.to_string(),
+                            _ => format!("{}-", key),
+                        }
+                    };
+                    let alternatives = vec![
+                        #(
+                            {
+                                let variant = #vnames;
+                                let _prefix = format!("{}{}", _prefix, variant);
+                                let mut spec = #specs;
+                                spec.doc = auto_args::doc_text(#variant_docs);
+                                spec
+                            },
+                        )*
+                    ];
+                    let mut spec = auto_args::ArgSpec::new(key, auto_args::ArgKind::Alternatives(alternatives));
+                    spec.doc = auto_args::doc_text(#doc);
+                    spec
+                }
             };
             s
         }
-        _ => panic!("AutoArgs only supports non-tuple structs"),
+        Union(_) => {
+            return Err(Error::new_spanned(
+                input,
+                "AutoArgs only supports structs and enums",
+            ))
+        }
     };
 
+    Ok(impl_auto_args(input, &container, myimpl))
+}
+
+/// Wrap the body of the `AutoArgs` impl with its generic bounds.
+fn impl_auto_args(
+    input: &DeriveInput,
+    container: &ContainerAttrs,
+    myimpl: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let name = &input.ident;
+    let completions = if container.completions {
+        quote!(const COMPLETIONS: bool = true;)
+    } else {
+        quote!()
+    };
+    let man = if container.man {
+        quote!(const MAN: bool = true;)
+    } else {
+        quote!()
+    };
+    let help_json = if container.help_json {
+        quote!(const HELP_JSON: bool = true;)
+    } else {
+        quote!()
+    };
+    let program = match container.name {
+        Some(ref n) => quote!(const NAME: Option<&'static str> = Some(#n);),
+        None => quote!(),
+    };
+    let after_help = match container.after_help {
+        Some(ref a) => quote!(const AFTER_HELP: &'static str = #a;),
+        None => quote!(),
+    };
+    let generics = &input.generics;
     let generic_types = input.generics.type_params();
     let bounds = quote! {
         <#(#generic_types: auto_args::AutoArgs),*>
@@ -488,40 +1497,14 @@ pub fn auto_args(raw_input: proc_macro::TokenStream) -> proc_macro::TokenStream
     let tokens2: proc_macro2::TokenStream = quote! {
         #[allow(unreachable_code)]
         impl#bounds auto_args::AutoArgs for #name#generics {
+            #completions
+            #man
+            #help_json
+            #program
+            #after_help
             #myimpl
         }
     };
     // println!("\n\n{}", tokens2);
-    tokens2.into()
-}
-
-fn camel_case_to_kebab(name: &str) -> String {
-    if name.chars().next() == Some('_') {
-        "".to_string()
-    } else if name.contains('_') {
-        let mut out = name.to_string().replace("_", "-");
-        if out.chars().last() == Some('-') {
-            out.pop();
-        }
-        out
-    } else {
-        let mut out = String::new();
-        let mut am_on_cap = true;
-        for c in name.chars() {
-            if !am_on_cap && c.is_ascii_uppercase() {
-                out.push('-');
-            }
-            am_on_cap = c.is_ascii_uppercase();
-            out.push(c.to_ascii_lowercase());
-        }
-        out
-    }
-}
-
-fn snake_case_to_kebab(name: &str) -> String {
-    if name.chars().next() == Some('_') {
-        "".to_string()
-    } else {
-        name.to_string().replace("_", "-")
-    }
+    tokens2
 }
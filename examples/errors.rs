@@ -0,0 +1,38 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! How `from_args` reports errors.  Try
+//!
+//! ```sh
+//! cargo run --example errors -- --threads many --bogus
+//! ```
+//!
+//! and set `ERRORS_HOOK` to report them with `set_error_hook` instead.
+
+use auto_args::AutoArgs;
+
+#[derive(AutoArgs, Debug)]
+#[allow(dead_code)] // the fields are only printed
+struct Opt {
+    /// Number of threads
+    threads: u32,
+    /// The name
+    name: Option<String>,
+}
+
+fn main() {
+    if std::env::var_os("ERRORS_HOOK").is_some() {
+        auto_args::set_error_hook(|errors, _usage| {
+            eprintln!("{} errors:", errors.len());
+            for e in errors {
+                eprintln!("  {}", e.kind());
+            }
+        });
+    }
+    println!("{:?}", Opt::from_args());
+}
@@ -0,0 +1,632 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Shell completion scripts, generated from [`AutoArgs::spec`].
+//!
+//! A script is created by [`generate`], or by running a program with
+//! `--generate-completions SHELL` if its type has
+//! `#[auto_args(completions)]`.  For example, with bash
+//!
+//! ```bash
+//! myprog --generate-completions bash > /etc/bash_completion.d/myprog
+//! ```
+//!
+//! Such a script cannot know which flags remain valid given what has
+//! already been typed, such as once one variant of an enum has been
+//! chosen.  Alternatively, the shell can ask the program itself each
+//! time, by running it with the environment variable `COMPLETE` set to
+//! the name of the shell, followed by `--` and the words typed so far.
+//! The program then prints the [`candidates`] for the last word.  Run
+//! with `COMPLETE` set and no words, it prints a small script that
+//! does this, so with bash
+//!
+//! ```bash
+//! source <(COMPLETE=bash myprog)
+//! ```
+
+use crate::{ArgKind, ArgSpec, AutoArgs, Error};
+use std::ffi::OsString;
+
+/// A shell that we can generate completions for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    /// The Bourne again shell.
+    Bash,
+    /// The Z shell.
+    Zsh,
+    /// The friendly interactive shell.
+    Fish,
+}
+
+impl std::str::FromStr for Shell {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(Error::OptionValueParsingFailed {
+                flag: "--generate-completions".to_string(),
+                value: s.to_string(),
+                expected: "(bash|zsh|fish)".to_string(),
+                index: None,
+                env: None,
+                source: None,
+            }),
+        }
+    }
+}
+
+/// Generate a completion script for `shell`, for the command `program`
+/// whose arguments are parsed as a `T`.
+pub fn generate<T: AutoArgs>(shell: Shell, program: &str) -> String {
+    let model = Model::new(&T::spec(""));
+    match shell {
+        Shell::Bash => bash(&model, program),
+        Shell::Zsh => zsh(&model, program),
+        Shell::Fish => fish(&model, program),
+    }
+}
+
+/// A flag as far as completion is concerned.
+struct Flag {
+    name: String,
+    short: Option<char>,
+    doc: String,
+    takes_value: bool,
+    path: bool,
+    repeatable: bool,
+}
+
+impl Flag {
+    fn new(spec: &ArgSpec, metavar: Option<&str>, repeatable: bool) -> Self {
+        Flag {
+            name: spec.name.clone(),
+            short: spec.short,
+            doc: first_line(&spec.doc),
+            takes_value: metavar.is_some(),
+            path: metavar == Some("PATH"),
+            repeatable: repeatable || spec.repeatable,
+        }
+    }
+
+    /// The ways this flag can be written.
+    fn names(&self) -> Vec<String> {
+        let mut names = vec![self.name.clone()];
+        names.extend(self.short.map(|c| format!("-{}", c)));
+        names
+    }
+}
+
+/// The flags, subcommands and positional arguments of a command.
+#[derive(Default)]
+struct Model {
+    flags: Vec<Flag>,
+    commands: Vec<(String, String, Model)>,
+    files: bool,
+}
+
+impl Model {
+    fn new(spec: &ArgSpec) -> Self {
+        let mut model = Model::default();
+        model.collect(spec);
+        model
+    }
+
+    fn collect(&mut self, spec: &ArgSpec) {
+        match spec.kind {
+            ArgKind::Switch if !spec.positional => self.flags.push(Flag::new(spec, None, false)),
+            ArgKind::Switch => (),
+            ArgKind::Value { ref metavar } => {
+                if spec.positional {
+                    self.files |= metavar == "PATH";
+                } else if !spec.name.is_empty() {
+                    self.flags.push(Flag::new(spec, Some(metavar), false));
+                }
+            }
+            ArgKind::Group(ref specs) => {
+                for s in specs {
+                    self.collect(s);
+                }
+            }
+            ArgKind::Alternatives(ref specs) if spec.positional => {
+                for s in specs {
+                    self.commands
+                        .push((s.name.clone(), first_line(&s.doc), Model::new(s)));
+                }
+            }
+            ArgKind::Alternatives(ref specs) => {
+                for s in specs {
+                    // A variant with nothing required is chosen by
+                    // its own flag.
+                    if let ArgKind::Group(_) = s.kind {
+                        if s.optional && s.name.len() > 2 {
+                            self.flags.push(Flag::new(s, None, false));
+                        }
+                    }
+                    self.collect(s);
+                }
+            }
+        }
+    }
+}
+
+fn first_line(doc: &str) -> String {
+    doc.lines().next().unwrap_or("").trim().to_string()
+}
+
+/// The name of the shell function for `program`.
+fn function_name(program: &str) -> String {
+    let name: String = program
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("_{}", name)
+}
+
+fn bash(model: &Model, program: &str) -> String {
+    let function = function_name(program);
+    let words = |m: &Model| {
+        let mut words: Vec<String> = Vec::new();
+        for f in m.flags.iter() {
+            words.push(f.name.clone());
+            words.extend(f.short.map(|c| format!("-{}", c)));
+        }
+        words.extend(m.commands.iter().map(|c| c.0.clone()));
+        words.join(" ")
+    };
+    let values = |m: &Model| {
+        let mut out = String::new();
+        for f in m.flags.iter().filter(|f| f.takes_value) {
+            let mut pattern = f.name.clone();
+            if let Some(c) = f.short {
+                pattern.push_str(&format!("|-{}", c));
+            }
+            let reply = if f.path {
+                "COMPREPLY=( $(compgen -f -- \"$cur\") )"
+            } else {
+                "COMPREPLY=()"
+            };
+            out.push_str(&format!("        {}) {}; return 0 ;;\n", pattern, reply));
+        }
+        out
+    };
+    let files = |m: &Model| {
+        if m.files {
+            "    COMPREPLY+=( $(compgen -f -- \"$cur\") )\n"
+        } else {
+            ""
+        }
+    };
+
+    let mut out = format!("{}() {{\n", function);
+    out.push_str("    local cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    out.push_str("    local prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+    let mut all_values = values(model);
+    if model.commands.is_empty() {
+        out.push_str(&format!("    case \"$prev\" in\n{}    esac\n", all_values));
+        out.push_str(&format!(
+            "    COMPREPLY=( $(compgen -W \"{}\" -- \"$cur\") )\n",
+            words(model)
+        ));
+        out.push_str(files(model));
+    } else {
+        let names: Vec<&str> = model.commands.iter().map(|c| c.0.as_str()).collect();
+        out.push_str("    local command=\"\"\n");
+        out.push_str("    for word in \"${COMP_WORDS[@]:1:COMP_CWORD-1}\"; do\n");
+        out.push_str(&format!(
+            "        case \"$word\" in\n            {}) command=\"$word\"; break ;;\n        esac\n",
+            names.join("|")
+        ));
+        out.push_str("    done\n");
+        for (_, _, m) in model.commands.iter() {
+            all_values.push_str(&values(m));
+        }
+        out.push_str(&format!("    case \"$prev\" in\n{}    esac\n", all_values));
+        out.push_str("    case \"$command\" in\n");
+        for (name, _, m) in model.commands.iter() {
+            out.push_str(&format!(
+                "        {})\n            COMPREPLY=( $(compgen -W \"{}\" -- \"$cur\") )\n",
+                name,
+                words(m)
+            ));
+            if m.files {
+                out.push_str("            COMPREPLY+=( $(compgen -f -- \"$cur\") )\n");
+            }
+            out.push_str("            ;;\n");
+        }
+        out.push_str(&format!(
+            "        *)\n            COMPREPLY=( $(compgen -W \"{}\" -- \"$cur\") )\n            ;;\n",
+            words(model)
+        ));
+        out.push_str("    esac\n");
+    }
+    out.push_str("}\n");
+    out.push_str(&format!("complete -F {} {}\n", function, program));
+    out
+}
+
+/// Quote `s` for use in single quotes in zsh, as the description in an
+/// `_arguments` spec.
+fn zsh_doc(s: &str) -> String {
+    s.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+fn zsh_flags(model: &Model, indent: &str) -> String {
+    let mut out = String::new();
+    for f in model.flags.iter() {
+        let value = if !f.takes_value {
+            String::new()
+        } else if f.path {
+            ": :_files".to_string()
+        } else {
+            ": : ".to_string()
+        };
+        let names = match f.short {
+            Some(c) => format!("'(-{c} {n})'{{-{c},{n}}}", c = c, n = f.name),
+            None => f.name.clone(),
+        };
+        let spec = format!("[{}]{}", zsh_doc(&f.doc), value);
+        match f.short {
+            Some(_) => out.push_str(&format!("{}{}'{}' \\\n", indent, names, spec)),
+            None => out.push_str(&format!("{}'{}{}' \\\n", indent, names, spec)),
+        }
+    }
+    out
+}
+
+fn zsh(model: &Model, program: &str) -> String {
+    let function = function_name(program);
+    let mut out = format!("#compdef {}\n\n{}() {{\n", program, function);
+    if model.commands.is_empty() {
+        out.push_str("    _arguments -s \\\n");
+        out.push_str(&zsh_flags(model, "        "));
+        if model.files {
+            out.push_str("        '*: :_files' \\\n");
+        }
+        out.push_str("        && return 0\n");
+    } else {
+        out.push_str("    local context state state_descr line\n");
+        out.push_str("    typeset -A opt_args\n");
+        out.push_str("    _arguments -s \\\n");
+        out.push_str(&zsh_flags(model, "        "));
+        out.push_str("        '1: :->command' \\\n");
+        out.push_str("        '*:: :->args' \\\n");
+        out.push_str("        && return 0\n");
+        out.push_str("    case $state in\n");
+        out.push_str("        command)\n            _values 'command' \\\n");
+        for (name, doc, _) in model.commands.iter() {
+            out.push_str(&format!("                '{}[{}]' \\\n", name, zsh_doc(doc)));
+        }
+        out.push_str("            ;;\n");
+        out.push_str("        args)\n            case $line[1] in\n");
+        for (name, _, m) in model.commands.iter() {
+            out.push_str(&format!("                {})\n", name));
+            out.push_str("                    _arguments -s \\\n");
+            out.push_str(&zsh_flags(m, "                        "));
+            if m.files {
+                out.push_str("                        '*: :_files' \\\n");
+            }
+            out.push_str("                    ;;\n");
+        }
+        out.push_str("            esac\n            ;;\n");
+        out.push_str("    esac\n");
+    }
+    out.push_str("}\n\n");
+    out.push_str(&format!("{} \"$@\"\n", function));
+    out
+}
+
+/// Quote `s` in single quotes for fish.
+fn fish_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn fish_flags(model: &Model, program: &str, condition: &str) -> String {
+    let mut out = String::new();
+    for f in model.flags.iter() {
+        out.push_str(&format!("complete -c {}{}", program, condition));
+        match f.name.strip_prefix("--") {
+            Some(long) => out.push_str(&format!(" -l {}", long)),
+            None => out.push_str(&format!(" -o {}", f.name.trim_start_matches('-'))),
+        }
+        if let Some(c) = f.short {
+            out.push_str(&format!(" -s {}", c));
+        }
+        if f.takes_value {
+            out.push_str(" -r");
+            if f.path {
+                out.push_str(" -F");
+            }
+        }
+        if !f.doc.is_empty() {
+            out.push_str(&format!(" -d {}", fish_quote(&f.doc)));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn fish(model: &Model, program: &str) -> String {
+    let mut out = String::new();
+    if !model.files && model.commands.iter().all(|c| !c.2.files) {
+        out.push_str(&format!("complete -c {} -f\n", program));
+    }
+    out.push_str(&fish_flags(model, program, ""));
+    for (name, doc, m) in model.commands.iter() {
+        out.push_str(&format!(
+            "complete -c {} -n __fish_use_subcommand -f -a {}",
+            program, name
+        ));
+        if !doc.is_empty() {
+            out.push_str(&format!(" -d {}", fish_quote(doc)));
+        }
+        out.push('\n');
+        let condition = format!(" -n '__fish_seen_subcommand_from {}'", name);
+        out.push_str(&fish_flags(m, program, &condition));
+    }
+    out
+}
+
+/// The flags, subcommands and positional arguments that remain
+/// possible given the arguments typed so far.
+#[derive(Default)]
+struct Reach {
+    flags: Vec<Flag>,
+    commands: Vec<(String, String)>,
+    files: bool,
+    /// Where the arguments of the chosen subcommand start.
+    start: usize,
+    /// Whether a subcommand has been completed, so no more flags of
+    /// its parents apply.
+    done: bool,
+}
+
+impl Reach {
+    fn collect(&mut self, spec: &ArgSpec, typed: &[OsString], offset: usize, repeatable: bool) {
+        if self.done {
+            return;
+        }
+        let repeatable = repeatable || spec.repeatable;
+        match spec.kind {
+            ArgKind::Switch if !spec.positional => {
+                self.flags.push(Flag::new(spec, None, repeatable))
+            }
+            ArgKind::Switch => (),
+            ArgKind::Value { ref metavar } => {
+                if spec.positional {
+                    self.files |= metavar == "PATH";
+                } else if !spec.name.is_empty() {
+                    self.flags.push(Flag::new(spec, Some(metavar), repeatable));
+                }
+            }
+            ArgKind::Group(ref specs) => {
+                for s in specs {
+                    self.collect(s, typed, offset, repeatable);
+                }
+            }
+            ArgKind::Alternatives(ref specs) if spec.positional => {
+                let end = crate::end_of_flags(typed);
+                let chosen = typed[..end].iter().enumerate().find_map(|(i, word)| {
+                    specs.iter().find(|s| word == s.name.as_str()).map(|s| (i, s))
+                });
+                match chosen {
+                    Some((i, s)) => {
+                        *self = Reach {
+                            start: offset + i + 1,
+                            ..Reach::default()
+                        };
+                        self.collect(s, &typed[i + 1..], offset + i + 1, false);
+                        self.done = true;
+                    }
+                    None => self
+                        .commands
+                        .extend(specs.iter().map(|s| (s.name.clone(), first_line(&s.doc)))),
+                }
+            }
+            ArgKind::Alternatives(ref specs) => {
+                // Once a flag of one variant is given, the flags of the
+                // others are no longer valid.
+                let variant_flags: Vec<Vec<String>> = specs
+                    .iter()
+                    .map(|s| {
+                        let mut names = Vec::new();
+                        flag_names(s, &mut names);
+                        names
+                    })
+                    .collect();
+                let chosen = crate::choose_variant(typed, &variant_flags).ok().flatten();
+                for (i, s) in specs.iter().enumerate() {
+                    if chosen.is_some() && chosen != Some(i) {
+                        continue;
+                    }
+                    if let ArgKind::Group(_) = s.kind {
+                        if s.optional && s.name.len() > 2 {
+                            self.flags.push(Flag::new(s, None, repeatable));
+                        }
+                    }
+                    self.collect(s, typed, offset, repeatable);
+                }
+            }
+        }
+    }
+}
+
+/// Every way of writing the flags within `spec`.
+fn flag_names(spec: &ArgSpec, names: &mut Vec<String>) {
+    if spec.positional {
+        return;
+    }
+    if spec.name.starts_with('-') {
+        names.push(spec.name.clone());
+        names.extend(spec.short.map(|c| format!("-{}", c)));
+    }
+    match spec.kind {
+        ArgKind::Group(ref specs) | ArgKind::Alternatives(ref specs) => {
+            for s in specs {
+                flag_names(s, names);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// The files and directories whose names start with `current`.
+fn files(current: &str, out: &mut Vec<(String, String)>) {
+    let (dir, prefix) = match current.rfind('/') {
+        Some(i) => (&current[..=i], &current[i + 1..]),
+        None => ("", current),
+    };
+    let entries = match std::fs::read_dir(if dir.is_empty() { "." } else { dir }) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    let mut found: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            let slash = if e.path().is_dir() { "/" } else { "" };
+            Some(format!("{}{}{}", dir, name, slash))
+        })
+        .collect();
+    found.sort();
+    out.extend(found.into_iter().map(|f| (f, String::new())));
+}
+
+/// The possible completions of the last of `words`, each with a
+/// description, given the arguments before it.
+///
+/// The flags that are still possible are found from
+/// [`AutoArgs::spec`] in the same way as the arguments are parsed, so
+/// flags that have been given are not offered again unless they can
+/// be repeated, and once one variant of an enum is chosen the flags
+/// of the others are not offered.
+pub fn candidates<T: AutoArgs>(words: &[String]) -> Vec<(String, String)> {
+    let (current, typed) = match words.split_last() {
+        Some((current, typed)) => (current.as_str(), typed),
+        None => ("", words),
+    };
+    let typed: Vec<OsString> = typed.iter().map(OsString::from).collect();
+    let mut reach = Reach::default();
+    reach.collect(&T::spec(""), &typed, 0, false);
+    let typed = &typed[reach.start..];
+
+    let mut out = Vec::new();
+    if typed.iter().any(|w| w == "--") {
+        if reach.files {
+            files(current, &mut out);
+        }
+        return out;
+    }
+    if let Some(last) = typed.last() {
+        let value_of = reach
+            .flags
+            .iter()
+            .find(|f| f.takes_value && f.names().iter().any(|n| last == n.as_str()));
+        if let Some(f) = value_of {
+            if f.path {
+                files(current, &mut out);
+            }
+            return out;
+        }
+    }
+    for f in reach.flags.iter() {
+        if f.name.starts_with(current)
+            && (f.repeatable || crate::find_flag(typed, &f.names()).is_none())
+        {
+            out.push((f.name.clone(), f.doc.clone()));
+        }
+    }
+    if !current.starts_with('-') {
+        out.extend(
+            reach
+                .commands
+                .into_iter()
+                .filter(|(name, _)| name.starts_with(current)),
+        );
+        if reach.files {
+            files(current, &mut out);
+        }
+    }
+    out
+}
+
+/// The response to a shell that has run `program` with `COMPLETE`
+/// set.  Given `--` followed by the words typed so far, this is the
+/// [`candidates`] one per line, otherwise it is the [`dynamic`]
+/// script.
+pub fn respond<T: AutoArgs>(shell: Shell, program: &str, args: &[OsString]) -> String {
+    match args.split_first() {
+        Some((dashes, words)) if dashes == "--" => {
+            let words: Vec<String> = words
+                .iter()
+                .map(|w| w.to_string_lossy().into_owned())
+                .collect();
+            candidates::<T>(&words)
+                .into_iter()
+                .map(|(c, doc)| match shell {
+                    Shell::Bash => format!("{}\n", c),
+                    Shell::Zsh if doc.is_empty() => format!("{}\n", c.replace(':', "\\:")),
+                    Shell::Zsh => format!("{}:{}\n", c.replace(':', "\\:"), doc),
+                    Shell::Fish if doc.is_empty() => format!("{}\n", c),
+                    Shell::Fish => format!("{}\t{}\n", c, doc),
+                })
+                .collect()
+        }
+        _ => dynamic(shell, program),
+    }
+}
+
+/// A script for `shell` that completes the arguments of `program` by
+/// running it with `COMPLETE` set.
+pub fn dynamic(shell: Shell, program: &str) -> String {
+    let function = function_name(program);
+    match shell {
+        Shell::Bash => format!(
+            r#"{f}() {{
+    local IFS=$'\n'
+    COMPREPLY=( $(COMPLETE=bash {p} -- "${{COMP_WORDS[@]:1:COMP_CWORD}}") )
+    if [[ ${{#COMPREPLY[@]}} -eq 1 && ${{COMPREPLY[0]}} == */ ]]; then
+        compopt -o nospace
+    fi
+}}
+complete -F {f} {p}
+"#,
+            f = function,
+            p = program
+        ),
+        Shell::Zsh => format!(
+            r#"#compdef {p}
+
+{f}() {{
+    local -a candidates
+    candidates=(${{(f)"$(COMPLETE=zsh {p} -- "${{(@)words[2,CURRENT]}}")"}})
+    _describe '{p}' candidates
+}}
+
+if [ "$funcstack[1]" = "{f}" ]; then
+    {f} "$@"
+else
+    compdef {f} {p}
+fi
+"#,
+            f = function,
+            p = program
+        ),
+        Shell::Fish => format!(
+            "complete -c {p} -f -a '(env COMPLETE=fish {p} -- (commandline -opc)[2..-1] (commandline -ct))'\n",
+            p = program
+        ),
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reference documentation, generated from [`AutoArgs::spec`].
+//!
+//! The [`markdown`] is suitable for mdBook, and the [`html`] is a
+//! complete page for a static site.  Both give the usage, every flag
+//! and positional argument with the whole of its doc comment, its
+//! type and whether it is required, and the alternatives of each enum
+//! in a section of its own.
+
+use crate::{ArgKind, ArgSpec, AutoArgs};
+
+/// Create a reference page in markdown for the command whose arguments
+/// are parsed as a `T`.
+pub fn markdown<T: AutoArgs>() -> String {
+    let mut out = String::new();
+    for block in document::<T>() {
+        match block {
+            Block::Heading { level, text, code } => {
+                let text = if code { format!("`{}`", text) } else { text };
+                out.push_str(&format!("{} {}\n\n", "#".repeat(level.min(6)), text));
+            }
+            Block::Entry {
+                level,
+                names,
+                about,
+            } => {
+                let names: Vec<String> = names.iter().map(|n| format!("`{}`", n)).collect();
+                out.push_str(&format!(
+                    "{} {}\n\n*{}*\n\n",
+                    "#".repeat(level.min(6)),
+                    names.join(", "),
+                    about
+                ));
+            }
+            Block::Text(text) => {
+                out.push_str(&text);
+                out.push_str("\n\n");
+            }
+            Block::Code(code) => out.push_str(&format!("```text\n{}\n```\n\n", code)),
+        }
+    }
+    out
+}
+
+/// Create a reference page in HTML for the command whose arguments
+/// are parsed as a `T`.
+pub fn html<T: AutoArgs>() -> String {
+    let program = crate::program_name::<T>();
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n",
+        escape(&program)
+    );
+    for block in document::<T>() {
+        match block {
+            Block::Heading { level, text, code } => {
+                let level = level.min(6);
+                let inner = if code {
+                    format!("<code>{}</code>", escape(&text))
+                } else {
+                    escape(&text)
+                };
+                out.push_str(&format!(
+                    "<h{l} id=\"{}\">{}</h{l}>\n",
+                    anchor(&text),
+                    inner,
+                    l = level
+                ));
+            }
+            Block::Entry {
+                level,
+                names,
+                about,
+            } => {
+                let level = level.min(6);
+                let inner: Vec<String> = names
+                    .iter()
+                    .map(|n| format!("<code>{}</code>", escape(n)))
+                    .collect();
+                out.push_str(&format!(
+                    "<h{l} id=\"{}\">{}</h{l}>\n<p><em>{}</em></p>\n",
+                    anchor(&names[0]),
+                    inner.join(", "),
+                    escape(&about),
+                    l = level
+                ));
+            }
+            Block::Text(text) => {
+                for paragraph in text.split("\n\n") {
+                    out.push_str(&format!("<p>{}</p>\n", escape(paragraph)));
+                }
+            }
+            Block::Code(code) => out.push_str(&format!("<pre><code>{}</code></pre>\n", escape(&code))),
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// A part of a reference page, independent of its format.
+enum Block {
+    /// A heading, and whether its text is code.
+    Heading {
+        level: usize,
+        text: String,
+        code: bool,
+    },
+    /// A heading naming a flag or argument, with a description of its
+    /// type and whether it is required.
+    Entry {
+        level: usize,
+        names: Vec<String>,
+        about: String,
+    },
+    /// A doc comment, with paragraphs separated by blank lines.
+    Text(String),
+    Code(String),
+}
+
+fn document<T: AutoArgs>() -> Vec<Block> {
+    let program = crate::program_name::<T>();
+    let spec = T::spec("");
+    let mut out = vec![Block::Heading {
+        level: 1,
+        text: program.clone(),
+        code: false,
+    }];
+    if !spec.doc.is_empty() {
+        out.push(Block::Text(spec.doc.clone()));
+    }
+    out.push(Block::Heading {
+        level: 2,
+        text: "Usage".to_string(),
+        code: false,
+    });
+    out.push(Block::Code(
+        format!("{} {}", program, T::tiny_help_message("")).trim().to_string(),
+    ));
+
+    let parts = Parts::new(&spec);
+    for (title, entries) in [("Arguments", &parts.arguments), ("Options", &parts.options)] {
+        if !entries.is_empty() {
+            out.push(Block::Heading {
+                level: 2,
+                text: title.to_string(),
+                code: false,
+            });
+            for s in entries {
+                entry(s, 3, &mut out);
+            }
+        }
+    }
+    for s in parts.alternatives {
+        alternatives(s, 2, &mut out);
+    }
+    out
+}
+
+/// The arguments of a spec, not counting those within alternatives.
+#[derive(Default)]
+struct Parts<'a> {
+    arguments: Vec<&'a ArgSpec>,
+    options: Vec<&'a ArgSpec>,
+    alternatives: Vec<&'a ArgSpec>,
+}
+
+impl<'a> Parts<'a> {
+    fn new(spec: &'a ArgSpec) -> Self {
+        let mut parts = Parts::default();
+        parts.collect(spec);
+        parts
+    }
+
+    fn collect(&mut self, spec: &'a ArgSpec) {
+        match spec.kind {
+            ArgKind::Switch if spec.positional => (),
+            ArgKind::Switch => self.options.push(spec),
+            ArgKind::Value { .. } if spec.positional => self.arguments.push(spec),
+            ArgKind::Value { .. } if !spec.name.is_empty() => self.options.push(spec),
+            ArgKind::Value { .. } => (),
+            ArgKind::Group(ref specs) => {
+                for s in specs {
+                    self.collect(s);
+                }
+            }
+            ArgKind::Alternatives(_) => self.alternatives.push(spec),
+        }
+    }
+}
+
+fn entry(spec: &ArgSpec, level: usize, out: &mut Vec<Block>) {
+    let mut names = vec![spec.name.clone()];
+    names.extend(spec.short.map(|c| format!("-{}", c)));
+    let mut about = Vec::new();
+    match spec.kind {
+        ArgKind::Value { ref metavar } => {
+            about.push(metavar.clone());
+            about.push(if spec.optional { "optional" } else { "required" }.to_string());
+        }
+        _ => about.push("switch".to_string()),
+    }
+    if spec.repeatable {
+        about.push("repeatable".to_string());
+    }
+    out.push(Block::Entry {
+        level,
+        names,
+        about: about.join(", "),
+    });
+    if !spec.doc.is_empty() {
+        out.push(Block::Text(spec.doc.clone()));
+    }
+}
+
+/// A section for the alternatives `spec`, with a nested section for
+/// each of them.
+fn alternatives(spec: &ArgSpec, level: usize, out: &mut Vec<Block>) {
+    let (text, code) = if spec.positional {
+        ("Commands".to_string(), false)
+    } else if spec.name.is_empty() {
+        ("Alternatives".to_string(), false)
+    } else {
+        (spec.name.clone(), true)
+    };
+    out.push(Block::Heading { level, text, code });
+    if !spec.doc.is_empty() {
+        out.push(Block::Text(spec.doc.clone()));
+    }
+    let specs = match spec.kind {
+        ArgKind::Alternatives(ref specs) => specs,
+        _ => return,
+    };
+    if !spec.positional {
+        out.push(Block::Text(
+            "Only one of the following may be given.".to_string(),
+        ));
+    }
+    for s in specs {
+        out.push(Block::Heading {
+            level: level + 1,
+            text: s.name.clone(),
+            code: true,
+        });
+        if !s.doc.is_empty() {
+            out.push(Block::Text(s.doc.clone()));
+        }
+        if let ArgKind::Switch = s.kind {
+            continue;
+        }
+        let parts = Parts::new(s);
+        for e in parts.arguments.iter().chain(parts.options.iter()) {
+            entry(e, level + 2, out);
+        }
+        for a in parts.alternatives {
+            alternatives(a, level + 2, out);
+        }
+    }
+}
+
+/// An id for a heading, such as `shape-circle` for `--shape-circle`.
+fn anchor(text: &str) -> String {
+    text.chars()
+        .filter_map(|c| match c {
+            'a'..='z' | '0'..='9' | '-' | '_' => Some(c),
+            'A'..='Z' => Some(c.to_ascii_lowercase()),
+            ' ' => Some('-'),
+            _ => None,
+        })
+        .collect::<String>()
+        .trim_start_matches('-')
+        .to_string()
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
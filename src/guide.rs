@@ -20,19 +20,19 @@
 //! 
 //! #[derive(Debug, AutoArgs)]
 //! struct Opt {
-//! /// Filling fraction
-//! filling_fraction: f64,
-//! /// Number of atoms
-//! N: u32,
-//! /// Output directory, working directory if not present
-//! dir: Option<PathBuf>,
-//! /// Activate verbose printing
-//! verbose: bool,
+//!     /// Filling fraction
+//!     filling_fraction: f64,
+//!     /// Number of atoms
+//!     N: u32,
+//!     /// Output directory, working directory if not present
+//!     dir: Option<PathBuf>,
+//!     /// Activate verbose printing
+//!     verbose: bool,
 //! }
 //! 
 //! fn main() {
-//! let opt = Opt::from_args();
-//! println!("{:?}", opt);
+//!     let opt = Opt::from_args();
+//!     println!("{:?}", opt);
 //! }
 //! ```
 //! The remainder of this guide will give examples of how the
@@ -42,8 +42,9 @@
 //! `AutoArgs` and calling the `from_args` method of your type.
 //! I want to note that `AutoArgs` *almost always* produces long flags.
 //! This is because I feel that long flags are generally the easiest
-//! to use.  If you want to fine-tune your command-line interface,
-//! `AutoArgs` may not be for you.
+//! to use.  Short flags are only given to single-letter fields and to
+//! fields that ask for one.  If you want to fine-tune your
+//! command-line interface, `AutoArgs` may not be for you.
 //! ## Just a flag
 //! Most often, you will define a struct as your type.  We'll
 //! start out with the simplest case, which is a single boolean
@@ -56,16 +57,16 @@
 //! This gives the following usage.
 //! ```ignore
 //! USAGE:
-//!   create_guide-5214c7bad433b4e9  [--foo]
+//!   create_guide-85a5619ccf52eede  [--foo]
 //! 
 //! For more information try --help
 //! ```
 //! and the following help message.
 //! ```ignore
 //! USAGE:
-//!   create_guide-5214c7bad433b4e9  [--foo]
+//!   create_guide-85a5619ccf52eede  [--foo]
 //! 
-//!   [--foo] 
+//!   [--foo]
 //! 
 //! 
 //! For more information try --help
@@ -85,17 +86,17 @@
 //! This gives the following usage.
 //! ```ignore
 //! USAGE:
-//!   create_guide-5214c7bad433b4e9  [--verbose] [--T]
+//!   create_guide-85a5619ccf52eede  [--verbose] [--T]
 //! 
 //! For more information try --help
 //! ```
 //! and the following help message.
 //! ```ignore
 //! USAGE:
-//!   create_guide-5214c7bad433b4e9  [--verbose] [--T]
+//!   create_guide-85a5619ccf52eede  [--verbose] [--T]
 //! 
 //!   [--verbose] Print excess messages.
-//!   [--T]       The temperature.
+//!   -T, [--T]   The temperature.
 //! 
 //! 
 //! For more information try --help
@@ -103,6 +104,10 @@
 //! I would always documentation for actual projects, so I'll try
 //! to model that here, even though these examples are all
 //! fictitious.
+//! The whole of a doc comment is shown by `--help`, with its
+//! paragraphs and lists indented under the flag, while `-h` shows
+//! only the first line of each.  A doc comment may also be given
+//! as `#[doc = include_str!("...")]`.
 //! ## How the flag is determined
 //! We saw above that the flag just had `--` prepended to the
 //! field name.  The rule in general is only slightly more
@@ -120,7 +125,7 @@
 //! This gives the following usage.
 //! ```ignore
 //! USAGE:
-//!   create_guide-5214c7bad433b4e9  [--verbose] [--blue-is-nice-] [--min-T]
+//!   create_guide-85a5619ccf52eede  [--verbose] [--blue-is-nice-] [--min-T]
 //! 
 //!   [--verbose]       a simple word has "--" prepended to it.
 //!   [--blue-is-nice-] Underscores are replaced with "-" ...
@@ -132,6 +137,63 @@
 //! Thus you can create most any flag name you care for, and it is
 //! easy to tell which flag corresponds to which field in your
 //! struct.
+//! ## Renaming flags
+//! If you would rather not name your fields after your flags, you
+//! can give a flag name explicitly with `#[auto_args(rename =
+//! "...")]` on a field or variant.  The convention used for all
+//! the fields and variants of a type can be chosen with
+//! `#[auto_args(rename_all = "...")]` on the type itself, which
+//! accepts `"kebab-case"` (the default described above),
+//! `"snake_case"`, `"lowercase"` and `"verbatim"`.
+//! ```ignore
+//! #[derive(AutoArgs)]
+//! #[auto_args(rename_all = "lowercase")]
+//! struct Renamed {
+//!     /// The minimum temperature.
+//!     min_T: f64,
+//!     /// The number of atoms.
+//!     #[auto_args(rename = "atoms")]
+//!     N: u32,
+//! }
+//! ```
+//! This gives the following usage.
+//! ```ignore
+//! USAGE:
+//!   create_guide-85a5619ccf52eede  --min-t FLOAT --atoms u32
+//! 
+//!   --min-t FLOAT The minimum temperature.
+//!   --atoms u32   The number of atoms.
+//! 
+//! 
+//! For more information try --help
+//! ```
+//! ## Short flags
+//! A field whose flag is a single letter, like `N` below, may also
+//! be given with a single dash, as in `-N 100`.  Any other field
+//! can be given a short flag with `#[auto_args(short = 'x')]`.
+//! Boolean short flags may be combined as in `-vq`, and values may
+//! be attached as in `-N100`.
+//! ```ignore
+//! #[derive(AutoArgs)]
+//! struct Short {
+//!     /// The number of atoms.
+//!     N: u32,
+//!     /// Print excess messages.
+//!     #[auto_args(short = 'v')]
+//!     verbose: bool,
+//! }
+//! ```
+//! This gives the following usage.
+//! ```ignore
+//! USAGE:
+//!   create_guide-85a5619ccf52eede  --N u32 [--verbose]
+//! 
+//!   -N, --N u32     The number of atoms.
+//!   -v, [--verbose] Print excess messages.
+//! 
+//! 
+//! For more information try --help
+//! ```
 //! ## Other types
 //! You can add most standard library types to your struct,
 //! basically anything that can be read or parsed from a `&str`.
@@ -149,10 +211,10 @@
 //! This gives the following usage.
 //! ```ignore
 //! USAGE:
-//!   create_guide-5214c7bad433b4e9  --name STRING --T FLOAT --directory STRING
+//!   create_guide-85a5619ccf52eede  --name STRING --T FLOAT --directory STRING
 //! 
 //!   --name STRING      The name of the type
-//!   --T FLOAT          The temperature of the type
+//!   -T, --T FLOAT      The temperature of the type
 //!   --directory STRING The place where it is
 //! 
 //! 
@@ -183,7 +245,7 @@
 //! This gives the following usage.
 //! ```ignore
 //! USAGE:
-//!   create_guide-5214c7bad433b4e9  [--name STRING]
+//!   create_guide-85a5619ccf52eede  [--name STRING]
 //! 
 //!   [--name STRING] The name is an optional argument.
 //! 
@@ -191,6 +253,158 @@
 //! For more information try --help
 //! ```
 //! The value is then `None` if the user did not specify that flag.
+//! A flag that is given with a missing or invalid value is still
+//! an error.  An `Option` of a struct is all or nothing: giving
+//! some of its required flags without the others is an error that
+//! lists the ones that are missing.
+//! ## Default values
+//! If there is a sensible value to use when the user does not
+//! specify a flag, you can give it with `#[auto_args(default =
+//! ...)]`, or use `#[auto_args(default)]` to fall back on
+//! `Default::default()`.  The field then keeps its ordinary type.
+//! A `bool` flag is already false when it is not given, so it
+//! cannot have a default.
+//! ```ignore
+//! #[derive(AutoArgs)]
+//! struct Defaults {
+//!     /// The filling fraction.
+//!     #[auto_args(default = 0.5)]
+//!     filling_fraction: f64,
+//!     /// The number of steps.
+//!     #[auto_args(default)]
+//!     steps: u64,
+//! }
+//! ```
+//! This gives the following usage.
+//! ```ignore
+//! USAGE:
+//!   create_guide-85a5619ccf52eede  [--filling-fraction FLOAT] [--steps u64]
+//! 
+//!   --filling-fraction FLOAT The filling fraction. [default: 0.5]
+//!   --steps u64              The number of steps.
+//! 
+//! 
+//! For more information try --help
+//! ```
+//! ## Repeated flags
+//! Giving a flag that takes a single value more than once, such as
+//! `--seed 1 --seed 2`, is an error.  If you would rather the last
+//! occurrence win, as many Unix tools do, you can say so with
+//! `#[auto_args(repeated = "last")]`, either on a field or on the
+//! type to apply to all of its fields.  A field can go back to the
+//! default with `#[auto_args(repeated = "error")]`.  A `Vec` field
+//! collects every occurrence regardless.
+//! ```ignore
+//! #[derive(AutoArgs)]
+//! #[auto_args(repeated = "last")]
+//! struct Repeated {
+//!     /// The random seed.
+//!     seed: u64,
+//!     /// The output file.
+//!     #[auto_args(repeated = "error")]
+//!     output: String,
+//! }
+//! ```
+//! This gives the following usage.
+//! ```ignore
+//! USAGE:
+//!   create_guide-85a5619ccf52eede  --seed u64 --output STRING
+//! 
+//!   --seed u64      The random seed.
+//!   --output STRING The output file.
+//! 
+//! 
+//! For more information try --help
+//! ```
+//! ## Environment variables
+//! A field can fall back on an environment variable when its flag
+//! is not given, with `#[auto_args(env = "...")]`.  Alternatively,
+//! `#[auto_args(env_prefix = "...")]` on the type gives every
+//! field a variable named by the prefix followed by the field name
+//! in upper case.  A flag on the command line always wins over the
+//! environment.
+//! ```ignore
+//! #[derive(AutoArgs)]
+//! #[auto_args(env_prefix = "APP_")]
+//! struct Environment {
+//!     /// The number of threads.
+//!     #[auto_args(default = 1)]
+//!     threads: u32,
+//!     /// The output directory.
+//!     #[auto_args(env = "OUTPUT_DIR")]
+//!     output: Option<String>,
+//! }
+//! ```
+//! This gives the following usage.
+//! ```ignore
+//! USAGE:
+//!   create_guide-85a5619ccf52eede  [--threads u32] [--output STRING]
+//! 
+//!   --threads u32     The number of threads. [default: 1] [env: APP_THREADS]
+//!   [--output STRING] The output directory. [env: OUTPUT_DIR]
+//! 
+//! 
+//! For more information try --help
+//! ```
+//! ## Positional arguments
+//! A field marked `#[auto_args(positional)]` is given without a
+//! flag, and is shown in the usage by its name in upper case.
+//! Positional fields are filled in the order they are declared,
+//! from the arguments that are left once the flags have been
+//! parsed, so a `Vec` collects all that remain and belongs last.
+//! Anything after a `--` is positional, even if it looks like a
+//! flag.
+//! ```ignore
+//! #[derive(AutoArgs)]
+//! struct Positional {
+//!     /// Print each file as it is copied.
+//!     verbose: bool,
+//!     /// The file to copy.
+//!     #[auto_args(positional)]
+//!     input: std::path::PathBuf,
+//!     /// Where to copy it.
+//!     #[auto_args(positional)]
+//!     output: Vec<std::path::PathBuf>,
+//! }
+//! ```
+//! This gives the following usage.
+//! ```ignore
+//! USAGE:
+//!   create_guide-85a5619ccf52eede  [--verbose] INPUT OUTPUT...
+//! 
+//!   [--verbose] Print each file as it is copied.
+//!   INPUT       The file to copy.
+//!   OUTPUT      Where to copy it.
+//! 
+//! 
+//! For more information try --help
+//! ```
+//! If you are wrapping another program, a `Vec<OsString>` field
+//! marked `#[auto_args(trailing)]` receives everything after the
+//! first `--` exactly as given, even arguments that look like
+//! flags.
+//! ```ignore
+//! #[derive(AutoArgs)]
+//! struct Trailing {
+//!     /// The program to run.
+//!     #[auto_args(positional)]
+//!     program: String,
+//!     /// Arguments for the program.
+//!     #[auto_args(trailing)]
+//!     args: Vec<std::ffi::OsString>,
+//! }
+//! ```
+//! This gives the following usage.
+//! ```ignore
+//! USAGE:
+//!   create_guide-85a5619ccf52eede  PROGRAM [-- ARGS...]
+//! 
+//!   PROGRAM      The program to run.
+//!   [-- ARGS...] Arguments for the program.
+//! 
+//! 
+//! For more information try --help
+//! ```
 //! ## Exclusive flags
 //! If you want to make certain flags/values mutually exclusive,
 //! you use an `enum` (just as always, in rust).
@@ -201,7 +415,7 @@
 //!     First {
 //!         /// This is the "a" value
 //!         a: String,
-//!         /// Only the first line of comment shows up in help.
+//!         /// Only the first line of comment shows up with `-h`.
 //!         /// This is the "b" value, which you cannot specify unless
 //!         /// you also specify the "a" value.
 //!         b: String,
@@ -215,23 +429,25 @@
 //! This gives the following usage.
 //! ```ignore
 //! USAGE:
-//!   create_guide-5214c7bad433b4e9 (  --first-a STRING --first-b STRING | --second-flag STRING | --Third )
+//!   create_guide-85a5619ccf52eede (  --first-a STRING --first-b STRING | --second-flag STRING | --Third )
 //! 
 //! For more information try --help
 //! ```
 //! and the following help message.
 //! ```ignore
 //! USAGE:
-//!   create_guide-5214c7bad433b4e9 (  --first-a STRING --first-b STRING | --second-flag STRING | --Third )
+//!   create_guide-85a5619ccf52eede (  --first-a STRING --first-b STRING | --second-flag STRING | --Third )
 //! 
-//!   EITHER               
+//!   EITHER
 //!   --first-a STRING     This is the "a" value
-//!   --first-b STRING     Only the first line of comment shows up in help.
-//!   OR                   
+//!   --first-b STRING     Only the first line of comment shows up with `-h`.
+//!                        This is the "b" value, which you cannot specify unless
+//!                        you also specify the "a" value.
+//!   OR
 //!   --second-flag STRING A string that cannot be used with any other flag
-//!   OR                   
+//!   OR
 //!   --Third              A flag with no value, and with a capital letter.
-//!                        
+//! 
 //! 
 //! 
 //! For more information try --help
@@ -239,9 +455,11 @@
 //! This example illustrates the three kinds of `enum` variants.
 //! Sadly, the help message does not indicate that these flags are
 //! exlusive.  However, if a user tries to specify both `--third`
-//! and `--second FOO`, however, they will get a nice error
-//! message.  Note that you cannot use a tuple variant with more
-//! than one field.
+//! and `--second-flag FOO`, they will get an error saying that the two
+//! cannot be used together.  The variant is chosen by which flags
+//! are given, so if only `--first-a` is given, the error will
+//! be that `--first-b` is missing.  Note that you cannot use
+//! a tuple variant with more than one field.
 //! Note that the rules for constructing flags from enum variants
 //! are more complicated than for struct fields.  This is because
 //! by convention variants are given `CamelCase` names, which
@@ -249,6 +467,47 @@
 //! underscore, then it is treated like a field name (as described
 //! above), with any trailing underscores removed.  Otherwise the
 //! name is converted from `CamelCase` to `kebab-case`.
+//! ## Subcommands
+//! An `enum` marked `#[auto_args(subcommand)]` is instead
+//! selected by a bare word, as in `git commit`.  The fields of
+//! the chosen variant are given after that word, with no prefix
+//! on their flags, and the variants are listed in the help along
+//! with their doc comments.  A field holding the subcommand is
+//! best placed after any flags of its own struct.
+//! ```ignore
+//! #[derive(AutoArgs)]
+//! #[auto_args(subcommand)]
+//! enum Command {
+//!     /// Build the project.
+//!     Build {
+//!         /// Build with optimizations.
+//!         release: bool,
+//!     },
+//!     /// Remove build products.
+//!     Clean,
+//! }
+//! #[derive(AutoArgs)]
+//! struct Tool {
+//!     /// Print more information.
+//!     verbose: bool,
+//!     command: Command,
+//! }
+//! ```
+//! This gives the following usage.
+//! ```ignore
+//! USAGE:
+//!   create_guide-85a5619ccf52eede  [--verbose] COMMAND
+//! 
+//!   [--verbose]       Print more information.
+//!   build [--release] Build with optimizations.
+//! 
+//! 
+//! COMMANDS:
+//!   build Build the project.
+//!   clean Remove build products.
+//! 
+//! For more information try --help
+//! ```
 //! ## Nesting types
 //! You can use any `AutoArgs` type as a field within a struct or
 //! enum.  Doing so will give flag names that combine the nested
@@ -276,12 +535,12 @@
 //! This gives the following usage.
 //! ```ignore
 //! USAGE:
-//!   create_guide-5214c7bad433b4e9   --position-x FLOAT --position-y FLOAT  --velocity-x FLOAT --velocity-y FLOAT
+//!   create_guide-85a5619ccf52eede   --position-x FLOAT --position-y FLOAT  --velocity-x FLOAT --velocity-y FLOAT
 //! 
-//!   --position-x FLOAT 
-//!   --position-y FLOAT 
-//!   --velocity-x FLOAT 
-//!   --velocity-y FLOAT 
+//!   --position-x FLOAT
+//!   --position-y FLOAT
+//!   --velocity-x FLOAT
+//!   --velocity-y FLOAT
 //! 
 //! 
 //! For more information try --help
@@ -292,9 +551,15 @@
 //! however, you would like to nest structs for a different
 //! reason: to separate concerns in the code.  In this case, you
 //! may not want the nesting to be visible in the user interface.
-//! This can be acheived with a leading underscore on a field
-//! name.  The catch is that when you do this, you could run into
-//! a runtime error if you have duplicate field names.
+//! This can be acheived with `#[auto_args(flatten)]` on a field
+//! (or on an enum variant).  A leading underscore on a field name
+//! does the same thing.  The catch is that when you do this, you
+//! could end up with two fields given the same flag.  `AutoArgs`
+//! catches duplicates among the fields of one type, and flattening
+//! the same type twice, at compile time.  Those within different
+//! flattened types panic when your program first parses its
+//! arguments or shows its help.  The variants of an enum may share
+//! flags, since only one of them is given.
 //! ```ignore
 //! #[derive(AutoArgs)]
 //! struct MyConfig {
@@ -308,21 +573,23 @@
 //! }
 //! #[derive(AutoArgs)]
 //! struct Flattened {
-//!     _mine: MyConfig,
-//!     _yours: YourConfig,
+//!     #[auto_args(flatten)]
+//!     mine: MyConfig,
+//!     #[auto_args(flatten)]
+//!     yours: YourConfig,
 //! }
 //! ```
 //! This gives the following usage.
 //! ```ignore
 //! USAGE:
-//!   create_guide-5214c7bad433b4e9   --name STRING  --address STRING
+//!   create_guide-85a5619ccf52eede   --name STRING  --address STRING
 //! 
 //! For more information try --help
 //! ```
 //! and the following help message.
 //! ```ignore
 //! USAGE:
-//!   create_guide-5214c7bad433b4e9   --name STRING  --address STRING
+//!   create_guide-85a5619ccf52eede   --name STRING  --address STRING
 //! 
 //!   --name STRING    The user's name
 //!   --address STRING The user's address
@@ -332,12 +599,100 @@
 //! ```
 //! This may be a good idea if `MyConfig` and `YourConfig` are
 //! implementation details that your user need not be aware of.
+//! ## Describing the program
+//! The doc comment on the type you parse describes the whole
+//! program, and is shown at the top of the help (only its first
+//! line for `-h`).  The name of the program is normally the one it
+//! was run by, but may be given with `#[auto_args(name = "...")]`,
+//! or at run time with `auto_args::set_program_name`, so that the
+//! help does not change when the program is run through a symlink.
+//! Any text in `#[auto_args(after_help = "...")]` goes at the end
+//! of the help.
+//! ```ignore
+//! #[derive(AutoArgs)]
+//! /// Count the words in files.
+//! #[auto_args(name = "wc", after_help = "Report bugs to the author.")]
+//! struct About {
+//!     /// Count lines instead.
+//!     lines: bool,
+//! }
+//! ```
+//! This gives the following help message.
+//! ```ignore
+//! Count the words in files.
+//! 
+//! USAGE:
+//!   wc  [--lines]
+//! 
+//!   [--lines] Count lines instead.
+//! 
+//! 
+//! Report bugs to the author.
+//! 
+//! For more information try --help
+//! ```
+//! ## Shell completions
+//! With `#[auto_args(completions)]` on the type you parse,
+//! `from_args` accepts a hidden `--generate-completions SHELL`
+//! flag, which prints a completion script for bash, zsh or fish
+//! and exits.  The script offers every flag along with the first
+//! line of its doc comment, the variants of subcommands, and file
+//! names for `PathBuf` values.  You can also create the script
+//! yourself with `auto_args::completions::generate`.
+//! ```ignore
+//! #[derive(AutoArgs)]
+//! #[auto_args(completions)]
+//! struct Completions {
+//!     /// Print more information.
+//!     verbose: bool,
+//!     /// The file to read.
+//!     input: std::path::PathBuf,
+//! }
+//! ```
+//! A user of bash could then run
+//! `myprog --generate-completions bash > ~/.local/share/bash-completion/completions/myprog`.
+//! Such a script offers every flag, even those that can no longer
+//! be given.  The same attribute lets the shell instead ask your
+//! program for the completions each time, which takes into account
+//! what has already been typed, such as which variant of an enum
+//! was chosen.  The user of bash would then run
+//! `source <(COMPLETE=bash myprog)`, and likewise for zsh and fish.
+//! ## Man pages
+//! Similarly, `#[auto_args(man)]` gives `from_args` a hidden
+//! `--generate-man` flag, which prints a man page and exits, as
+//! does `auto_args::man::render`.  The description is taken from
+//! the doc comment on the type, and unlike in the help every line
+//! of the doc comment on each flag is included.  The variants of
+//! an enum, and subcommands, are listed in sections of their own.
+//! ```ignore
+//! #[derive(AutoArgs)]
+//! #[auto_args(man)]
+//! /// Copy a file.
+//! ///
+//! /// The copy is made atomically, by writing to a temporary file.
+//! struct Manual {
+//!     /// Print more information.
+//!     ///
+//!     /// This reports the number of bytes copied.
+//!     verbose: bool,
+//! }
+//! ```
+//! ## Reference documentation
+//! For a book or web site, `auto_args::docs::markdown` and
+//! `auto_args::docs::html` create a reference page giving the
+//! usage, every flag with the whole of its doc comment, its type
+//! and whether it is required, and the alternatives of each enum
+//! in a nested section.
+//! A program that builds a user interface from your flags can
+//! instead run yours with `--help-json`, which prints a versioned
+//! JSON description of every argument, if you put
+//! `#[auto_args(help_json)]` on the type you parse.
 //! ## Other possibilities
 //! There may be a few other features that auto_args has, for which I
 //! have not bothered to create an entire example.  I will list
 //! them here when they come to mind.
 //! 1. You can use a `Vec<T>` for many values of `T` to create an
-//! option that can be specified more than once.
+//!    option that can be specified more than once.
 //! ## Conclusion
 //! There is more that could be said and more possible examples,
 //! but I think this is enough to get you started using `AutoArgs`.
@@ -0,0 +1,236 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Laying out the help in columns on the terminal.
+
+/// The width of the terminal if we cannot find it.
+const DEFAULT_WIDTH: usize = 80;
+
+/// The narrowest we will wrap a description, however long the flags.
+const MIN_DESCRIPTION: usize = 20;
+
+/// The number of columns that `s` takes up on a terminal.
+///
+/// Combining marks take up none, and East Asian wide characters and
+/// emoji take up two.
+pub fn display_width(s: &str) -> usize {
+    unicode_width::UnicodeWidthStr::width(s)
+}
+
+/// The width of the terminal, from `COLUMNS` if it is set, or else
+/// from the terminal itself.
+pub fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.trim().parse().ok())
+        .filter(|&c: &usize| c > 0)
+        .or_else(tty_width)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+#[cfg(any(unix, windows))]
+fn tty_width() -> Option<usize> {
+    // The help goes to stdout and the usage to stderr, either of
+    // which may be the terminal.
+    terminal_size::terminal_size()
+        .or_else(|| terminal_size::terminal_size_of(std::io::stderr()))
+        .map(|(terminal_size::Width(w), _)| w as usize)
+        .filter(|&w| w > 0)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn tty_width() -> Option<usize> {
+    None
+}
+
+/// Align the lines of `inp` of the form `indent\tflag\tdescription`
+/// into columns, leaving any other lines as they are.  A line with
+/// neither indent nor flag continues the description above it.
+///
+/// The descriptions are wrapped to fit in `width` columns, with any
+/// wrapped lines hanging under the start of the description.  A flag
+/// too long to leave room for its description is put on a line of its
+/// own, with the description below.
+pub fn align_tabs(inp: &str, width: usize) -> String {
+    let rows: Vec<Vec<&str>> = inp.lines().map(|l| l.splitn(3, '\t').collect()).collect();
+    let mut stop1 = 0;
+    for v in rows.iter().filter(|v| v.len() > 2) {
+        stop1 = std::cmp::max(stop1, display_width(v[0]) + 2);
+    }
+    // The description starts after the longest flag that leaves enough
+    // room for it.
+    let limit = std::cmp::max(width * 2 / 5, stop1 + 1);
+    let column = rows
+        .iter()
+        .filter(|v| v.len() > 2)
+        .map(|v| stop1 + display_width(v[1]) + 1)
+        .filter(|&w| w <= limit)
+        .max()
+        .unwrap_or(limit);
+    let available = std::cmp::max(width.saturating_sub(column), MIN_DESCRIPTION);
+
+    let mut out = String::with_capacity(inp.len());
+    for v in rows.iter() {
+        if v.len() <= 2 {
+            out.push_str(&v.join("\t"));
+            out.push('\n');
+            continue;
+        }
+        if v[0].is_empty() && v[1].is_empty() {
+            for line in wrap(v[2], available) {
+                if !line.is_empty() {
+                    out.push_str(&" ".repeat(column));
+                    out.push_str(&line);
+                }
+                out.push('\n');
+            }
+            continue;
+        }
+        let flag = format!("{}{}", pad(v[0], stop1), v[1]);
+        let mut lines = wrap(v[2], available).into_iter();
+        if v[2].is_empty() {
+            out.push_str(&flag);
+            lines.next();
+        } else if stop1 + display_width(v[1]) < column {
+            out.push_str(&pad(&flag, column));
+            out.push_str(&lines.next().unwrap_or_default());
+        } else {
+            out.push_str(&flag);
+        }
+        out.push('\n');
+        for line in lines {
+            out.push_str(&" ".repeat(column));
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// `s` followed by spaces to make it `width` columns wide.
+fn pad(s: &str, width: usize) -> String {
+    let w = display_width(s);
+    format!("{}{}", s, " ".repeat(width.saturating_sub(w)))
+}
+
+/// Split each line of `text` into lines of at most `width` columns,
+/// breaking between words.  A word too long for a line has one to
+/// itself, and the lines a line is wrapped into keep its indentation.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for text in text.split('\n') {
+        if display_width(text) <= width {
+            lines.push(text.to_string());
+            continue;
+        }
+        let words = text.trim_start();
+        let indent = &text[..text.len() - words.len()];
+        let start = display_width(indent);
+        let mut line = indent.to_string();
+        let mut line_width = start;
+        for word in words.split_whitespace() {
+            let w = display_width(word);
+            if line_width > start && line_width + 1 + w > width {
+                lines.push(std::mem::replace(&mut line, indent.to_string()));
+                line_width = start;
+            }
+            if line_width > start {
+                line.push(' ');
+                line_width += 1;
+            }
+            line.push_str(word);
+            line_width += w;
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widths() {
+        assert_eq!(display_width("--verbose"), 9);
+        assert_eq!(display_width("caf\u{e9}"), 4);
+        assert_eq!(display_width("cafe\u{301}"), 4);
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(display_width("\u{1f680} \u{2614}"), 5);
+        assert_eq!(display_width("\u{a960}"), 2);
+    }
+
+    #[test]
+    fn unicode_alignment() {
+        assert_eq!(
+            align_tabs("\t--naïve\tFirst\n\t--日本\tSecond\n", 80),
+            "  --naïve First\n  --日本  Second\n"
+        );
+    }
+
+    #[test]
+    fn other_lines() {
+        assert_eq!(
+            align_tabs("USAGE:\n\t--a\tThe a\n\n", 80),
+            "USAGE:\n  --a The a\n\n"
+        );
+    }
+
+    #[test]
+    fn wrapping() {
+        assert_eq!(
+            align_tabs(
+                "\t--threads u32\tThe number of threads to use for the computation\n",
+                40
+            ),
+            "  --threads u32 The number of threads to\n                use for the computation\n"
+        );
+    }
+
+    #[test]
+    fn continued() {
+        assert_eq!(
+            align_tabs("\t--n u32\tHow many\n\t\t\n\t\tAt least one.\n", 80),
+            "  --n u32 How many\n\n          At least one.\n"
+        );
+    }
+
+    #[test]
+    fn stacked() {
+        assert_eq!(
+            align_tabs(
+                "\t--n u32\tHow many\n\t--a-very-long-flag-name-indeed STRING\tThe name\n",
+                40
+            ),
+            "  --n u32 How many\n  --a-very-long-flag-name-indeed STRING\n          The name\n"
+        );
+    }
+
+    #[test]
+    fn without_description() {
+        assert_eq!(
+            align_tabs(
+                "\t--n u32\t\n\t--a-very-long-flag-name-indeed STRING\t\n\t--b\tThe b\n",
+                40
+            ),
+            "  --n u32\n  --a-very-long-flag-name-indeed STRING\n  --b     The b\n"
+        );
+    }
+
+    #[test]
+    fn indented() {
+        assert_eq!(
+            wrap("    let threads = 4; // the number of threads", 30),
+            vec!["    let threads = 4; // the", "    number of threads"]
+        );
+        assert_eq!(
+            align_tabs("\t--n u32\tHow many\n\t\t  for example twenty or thirty\n", 30),
+            "  --n u32 How many\n            for example twenty\n            or thirty\n"
+        );
+    }
+}
@@ -14,77 +14,476 @@ use std::path::PathBuf;
 #[cfg(not(feature = "meval"))]
 use std::str::FromStr;
 
+pub mod completions;
+pub mod docs;
 pub mod guide;
+mod layout;
+pub mod man;
 
 #[doc(hidden)]
 pub use auto_args_derive::*;
 
+/// Align the columns of a help message to fit the terminal.
 fn align_tabs(inp: &str) -> String {
-    let mut out = String::with_capacity(inp.len());
-    let mut stop1 = 0;
-    let mut stop2 = 0;
-    for l in inp.lines() {
-        let v: Vec<_> = l.splitn(3, '\t').collect();
-        if v.len() > 2 {
-            stop1 = std::cmp::max(stop1, v[0].len() + 2);
-            stop2 = std::cmp::max(stop2, v[1].len() + 1);
-        }
-    }
-    // We now know where to align the columns.
-    for l in inp.lines() {
-        let v: Vec<_> = l.splitn(3, '\t').collect();
-        if v.len() > 2 {
-            out.push_str(&format!(
-                "{:a$}{:b$}{}\n",
-                v[0],
-                v[1],
-                v[2],
-                a = stop1,
-                b = stop2
-            ));
-        } else {
-            out.push_str(l);
-            out.push('\n');
+    layout::align_tabs(inp, layout::terminal_width())
+}
+
+/// The list of subcommands shown by `usage` and `help`, if there
+/// are any.
+fn commands_message(commands: &[(String, String)]) -> String {
+    if commands.is_empty() {
+        return String::new();
+    }
+    let mut table = String::new();
+    for (command, doc) in commands {
+        table.push_str(&format!("\t{}\t{}\n", command, doc));
+    }
+    format!("COMMANDS:\n{}\n", align_tabs(&table))
+}
+
+/// A description of the arguments accepted by a type, as given by
+/// [`AutoArgs::spec`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ArgSpec {
+    /// The flag, such as `--threads`, or for a positional argument the
+    /// name shown in the usage.
+    pub name: String,
+    /// The short flag, if any.
+    pub short: Option<char>,
+    /// The doc comment.
+    pub doc: String,
+    /// Whether this may be left out.
+    pub optional: bool,
+    /// Whether this may be given more than once.
+    pub repeatable: bool,
+    /// Whether this is given without a flag.
+    pub positional: bool,
+    /// What sort of argument this is.
+    pub kind: ArgKind,
+}
+
+/// What sort of argument an [`ArgSpec`] describes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgKind {
+    /// A flag with no value, which is either given or not.
+    Switch,
+    /// A flag or positional argument with a value.
+    Value {
+        /// The kind of value, as shown in the usage, such as `u32`.
+        metavar: String,
+    },
+    /// A struct, all of whose arguments may be given.
+    Group(Vec<ArgSpec>),
+    /// An enum, only one of whose alternatives may be given.
+    Alternatives(Vec<ArgSpec>),
+}
+
+impl ArgSpec {
+    /// A required argument called `name`, with no doc comment.
+    pub fn new(name: impl Into<String>, kind: ArgKind) -> Self {
+        ArgSpec {
+            name: name.into(),
+            short: None,
+            doc: String::new(),
+            optional: false,
+            repeatable: false,
+            positional: false,
+            kind,
+        }
+    }
+
+    /// A required argument called `name`, taking a `metavar` value.
+    pub fn value(name: impl Into<String>, metavar: impl Into<String>) -> Self {
+        ArgSpec::new(
+            name,
+            ArgKind::Value {
+                metavar: metavar.into(),
+            },
+        )
+    }
+
+    /// This argument, but given any number of times.
+    fn repeated(mut self) -> Self {
+        self.optional = true;
+        self.repeatable = true;
+        self
+    }
+
+    /// This spec as a JSON object.
+    ///
+    /// Every object has the keys `name`, `short`, `doc`, `optional`,
+    /// `repeatable`, `positional`, `kind` (one of `switch`, `value`,
+    /// `group` and `alternatives`), `metavar` and `children`.  The
+    /// `short` and `metavar` are `null` where they do not apply, and
+    /// `children` is empty except for a group or alternatives.
+    pub fn to_json(&self) -> String {
+        let (kind, metavar, children) = match self.kind {
+            ArgKind::Switch => ("switch", "null".to_string(), &[][..]),
+            ArgKind::Value { ref metavar } => ("value", json_string(metavar), &[][..]),
+            ArgKind::Group(ref specs) => ("group", "null".to_string(), &specs[..]),
+            ArgKind::Alternatives(ref specs) => ("alternatives", "null".to_string(), &specs[..]),
+        };
+        let children: Vec<String> = children.iter().map(|s| s.to_json()).collect();
+        format!(
+            "{{\"name\":{},\"short\":{},\"doc\":{},\"optional\":{},\"repeatable\":{},\"positional\":{},\"kind\":{},\"metavar\":{},\"children\":[{}]}}",
+            json_string(&self.name),
+            self.short
+                .map_or_else(|| "null".to_string(), |c| json_string(&c.to_string())),
+            json_string(&self.doc),
+            self.optional,
+            self.repeatable,
+            self.positional,
+            json_string(kind),
+            metavar,
+            children.join(",")
+        )
+    }
+}
+
+/// The version of the JSON given by [`AutoArgs::help_json`], which
+/// will change only if its format does.
+pub const HELP_JSON_VERSION: u32 = 1;
+
+/// A function to report errors from [`AutoArgs::from_args`], as set by
+/// [`set_error_hook`].
+type ErrorHook = Box<dyn Fn(&[Error], &str) + Send + Sync>;
+
+static ERROR_HOOK: std::sync::RwLock<Option<ErrorHook>> = std::sync::RwLock::new(None);
+
+/// Set how [`AutoArgs::from_args`] reports errors before exiting.
+///
+/// The hook is given every error and the usage message, and replaces
+/// the default of printing them to stderr.  This allows an application
+/// to log, colorize or translate the errors.  We still exit with
+/// status 2 after the hook returns.
+pub fn set_error_hook(hook: impl Fn(&[Error], &str) + Send + Sync + 'static) {
+    *ERROR_HOOK.write().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(hook));
+}
+
+/// How [`AutoArgs::from_args_with`] prints errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// Messages for a person to read, followed by the usage.
+    Human,
+    /// A single line of JSON, holding an object with an `errors` array
+    /// of [`Error::to_json`] objects and the `usage`.
+    Json,
+}
+
+impl ErrorFormat {
+    /// `Json` if the environment variable `AUTO_ARGS_ERROR_FORMAT` is
+    /// `json`, and otherwise `Human`.
+    pub fn from_env() -> Self {
+        match std::env::var_os("AUTO_ARGS_ERROR_FORMAT") {
+            Some(ref f) if f == "json" => ErrorFormat::Json,
+            _ => ErrorFormat::Human,
+        }
+    }
+}
+
+fn report_errors(errors: &[Error], usage: &str, format: ErrorFormat) {
+    match *ERROR_HOOK.read().unwrap_or_else(|e| e.into_inner()) {
+        Some(ref hook) => hook(errors, usage),
+        None => match format {
+            ErrorFormat::Human => {
+                for e in errors {
+                    eprintln!("error: {}\n", e);
+                }
+                eprintln!("{}", usage);
+            }
+            ErrorFormat::Json => {
+                let errors: Vec<String> = errors.iter().map(|e| e.to_json()).collect();
+                eprintln!(
+                    "{{\"errors\":[{}],\"usage\":{}}}",
+                    errors.join(","),
+                    json_string(usage)
+                );
+            }
+        },
+    }
+}
+
+/// A string as a JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// The name of this program, as shown in the usage.
+static PROGRAM_NAME: std::sync::RwLock<Option<String>> = std::sync::RwLock::new(None);
+
+/// Set the name of the program as shown in the help, the usage, man
+/// pages and the like, in place of the name it was run by.
+///
+/// This takes precedence over `#[auto_args(name = "...")]`, and keeps
+/// the help the same in tests or when the program is run through a
+/// symlink.
+pub fn set_program_name(name: impl Into<String>) {
+    *PROGRAM_NAME.write().unwrap_or_else(|e| e.into_inner()) = Some(name.into());
+}
+
+/// The name of the program, as set by [`set_program_name`] or
+/// `T::NAME`, or else as it was run.
+fn program_name<T: AutoArgs>() -> String {
+    if let Some(name) = PROGRAM_NAME.read().unwrap_or_else(|e| e.into_inner()).clone() {
+        return name;
+    }
+    if let Some(name) = T::NAME {
+        return name.to_string();
+    }
+    std::env::args_os()
+        .next()
+        .unwrap()
+        .to_string_lossy()
+        .rsplit("/")
+        .next()
+        .unwrap()
+        .to_string()
+}
+
+/// The shell given with `--generate-completions`, if any.
+fn asks_for_completions(args: &[OsString]) -> Option<Result<completions::Shell, Error>> {
+    let i = args[..end_of_flags(args)]
+        .iter()
+        .position(|v| v == "--generate-completions")?;
+    Some(match args.get(i + 1) {
+        Some(shell) => shell.to_string_lossy().parse(),
+        None => Err(Error::OptionWithoutAValue {
+            flag: "--generate-completions".to_string(),
+            expected: "(bash|zsh|fish)".to_string(),
+            index: None,
+        }),
+    })
+}
+
+/// The help asked for before any `--`, if any.  This is the full help
+/// for `--help`, or the short help for `-h` unless that is a short flag
+/// of `T`.
+fn requested_help<T: AutoArgs>(args: &[OsString]) -> Option<String> {
+    let args = &args[..end_of_flags(args)];
+    if args.iter().any(|v| v == "--help") {
+        Some(T::help())
+    } else if args.iter().any(|v| v == "-h") && !T::short_flags("").iter().any(|s| s.0 == 'h') {
+        Some(T::short_help())
+    } else {
+        None
+    }
+}
+
+/// For implementation, but not for using this library.
+///
+/// The text of a doc comment, given as its `doc` attributes joined by
+/// newlines, without the indentation common to its lines or any blank
+/// lines at its start or end.
+#[doc(hidden)]
+pub fn doc_text(raw: &str) -> String {
+    let lines: Vec<&str> = raw.lines().map(|l| l.trim_end()).collect();
+    let indent = lines
+        .iter()
+        .filter(|l| !l.is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let lines: Vec<&str> = lines.iter().map(|l| l.get(indent..).unwrap_or("")).collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+/// For implementation, but not for using this library.
+///
+/// The first line of a doc comment, which is all that the short help
+/// shows.
+#[doc(hidden)]
+pub fn doc_summary(raw: &str) -> String {
+    doc_text(raw).lines().next().unwrap_or("").trim().to_string()
+}
+
+/// For implementation, but not for using this library.
+///
+/// A doc comment as given to `help_message`, with any `notes` (such as
+/// the default value) after its first line.  Each further line starts
+/// with two tabs, so that it is aligned under the first.
+#[doc(hidden)]
+pub fn help_doc(raw: &str, notes: &str) -> String {
+    let text = doc_text(raw);
+    let mut lines = text.lines();
+    let mut out = lines.next().unwrap_or("").to_string();
+    if !notes.is_empty() {
+        if !out.is_empty() {
+            out.push(' ');
         }
+        out.push_str(notes);
+    }
+    for line in lines {
+        out.push_str("\n\t\t");
+        out.push_str(line);
     }
     out
 }
 
+/// The help for `T`, either with every line of the doc comments or
+/// with only the first.
+fn help_text<T: AutoArgs>(full: bool) -> String {
+    let mut message = T::help_message("", "");
+    if !full {
+        message = message
+            .lines()
+            .filter(|l| !l.starts_with("\t\t"))
+            .map(|l| format!("{}\n", l))
+            .collect();
+    }
+    let spec = T::spec("");
+    check_flags(&spec);
+    let doc = spec.doc;
+    let about = if full { doc } else { doc_summary(&doc) };
+    let about = if about.is_empty() {
+        about
+    } else {
+        format!("{}\n\n", about)
+    };
+    let after = if T::AFTER_HELP.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n\n", doc_text(T::AFTER_HELP))
+    };
+    format!(
+        "{}USAGE:
+  {} {}
+
+{}
+
+{}{}For more information try --help",
+        about,
+        program_name::<T>(),
+        T::tiny_help_message(""),
+        align_tabs(&message),
+        commands_message(&T::subcommands()),
+        after
+    )
+}
+
+/// Whether `--help-json` is given before any `--`.
+fn asks_for_help_json(args: &[OsString]) -> bool {
+    args[..end_of_flags(args)].iter().any(|v| v == "--help-json")
+}
+
 /// The primary trait, which is implemented by any type which may be
 /// part of your command-line flags.
 pub trait AutoArgs: Sized {
     /// Parse the command-line arguments, exiting in case of error.
     ///
-    /// This is what users actually use.
+    /// This is what users actually use.  Given `--help` (or `-h`
+    /// for the short help), this prints the help and exits with
+    /// status 0, and likewise `--help-json` prints
+    /// [`AutoArgs::help_json`] for a type with
+    /// `#[auto_args(help_json)]`.  Otherwise any errors are
+    /// passed to the hook set by [`set_error_hook`], or else printed
+    /// to stderr along with the usage, and we exit with status 2.
+    ///
+    /// The errors are printed as JSON if the environment variable
+    /// `AUTO_ARGS_ERROR_FORMAT` is `json`.
     fn from_args() -> Self {
+        Self::from_args_with(ErrorFormat::from_env())
+    }
+    /// Parse the command-line arguments like `from_args`, but printing
+    /// any errors in the given format.
+    fn from_args_with(format: ErrorFormat) -> Self {
         let mut v: Vec<_> = std::env::args_os().collect();
         v.remove(0);
-        if v.iter().any(|v| v == "--help") {
-            println!("{}", Self::help());
+        let program = program_name::<Self>();
+        if Self::COMPLETIONS {
+            let shell = std::env::var("COMPLETE").ok().and_then(|s| s.parse().ok());
+            if let Some(shell) = shell {
+                print!("{}", completions::respond::<Self>(shell, &program, &v));
+                std::process::exit(0);
+            }
+        }
+        if let Some(help) = requested_help::<Self>(&v) {
+            println!("{}", help);
+            std::process::exit(0);
+        }
+        if Self::HELP_JSON && asks_for_help_json(&v) {
+            println!("{}", Self::help_json());
+            std::process::exit(0);
+        }
+        if Self::MAN && v[..end_of_flags(&v)].iter().any(|a| a == "--generate-man") {
+            print!("{}", man::render::<Self>());
             std::process::exit(0);
         }
-        match Self::parse_vec(v) {
+        if Self::COMPLETIONS {
+            if let Some(shell) = asks_for_completions(&v) {
+                match shell {
+                    Ok(shell) => {
+                        print!("{}", completions::generate::<Self>(shell, &program));
+                        std::process::exit(0);
+                    }
+                    Err(e) => {
+                        report_errors(&[e.locate(&located(v))], &Self::usage(), format);
+                        std::process::exit(2)
+                    }
+                }
+            }
+        }
+        match Self::parse_vec_all_errors(v) {
             Ok(val) => val,
-            Err(e) => {
-                println!("error: {}\n", e);
-                println!("{}", Self::usage());
-                std::process::exit(1)
+            Err(errors) => {
+                report_errors(&errors, &Self::usage(), format);
+                std::process::exit(2)
             }
         }
     }
+    /// Parse the command-line arguments, without exiting.
+    ///
+    /// Given `--help`, this returns `Error::HelpRequested`.
+    fn try_from_args() -> Result<Self, Error> {
+        Self::from_iter(std::env::args_os())
+    }
     /// Parse a `Vec` of arguments as if they were command line flags
     ///
     /// This mimics what we would do if we were doing the real
     /// parsing, except that we don't exit on error.
-    fn parse_vec(mut args: Vec<OsString>) -> Result<Self, Error> {
-        let v = Self::parse_internal("", &mut args)?;
-        if args.len() > 0 {
-            Err(Error::UnexpectedOption(format!("{:?}", args)))
-        } else {
-            Ok(v)
+    fn parse_vec(args: Vec<OsString>) -> Result<Self, Error> {
+        Self::parse_vec_all_errors(args).map_err(|mut errors| errors.remove(0))
+    }
+    /// Parse a `Vec` of arguments as if they were command line flags,
+    /// reporting every error rather than just the first.
+    fn parse_vec_all_errors(args: Vec<OsString>) -> Result<Self, Vec<Error>> {
+        let spec = Self::spec("");
+        check_flags(&spec);
+        let mut values = Vec::new();
+        value_flags(&spec, &mut values);
+        let argv = args.clone();
+        let located = expand_short_flags(&Self::short_flags(""), &values, located(args))
+            .map_err(|e| vec![e])?;
+        let located = join_values(&values, located);
+        let mut args: Vec<OsString> = located.iter().map(|a| a.arg.clone()).collect();
+        let (v, mut errors) = match Self::parse_internal_all("", &mut args) {
+            Ok(v) => (Some(v), Vec::new()),
+            Err(errors) => (None, errors),
+        };
+        let at = leftover_positions(&located, &args);
+        errors.extend(leftover_errors(&args, &at, &argv, &Self::flag_names("")));
+        match v {
+            Some(v) if errors.is_empty() => Ok(v),
+            _ => Err(errors.into_iter().map(|e| e.locate(&located)).collect()),
         }
     }
     /// Parse arguments given through an iterable thing such as a `Vec` or a slice, ignoring first element.
+    ///
+    /// Given `--help`, this returns `Error::HelpRequested`.
     fn from_iter<I, T>(args: I) -> Result<Self, Error>
     where
         I: IntoIterator<Item = T>,
@@ -92,6 +491,9 @@ pub trait AutoArgs: Sized {
     {
         let mut v: Vec<_> = args.into_iter().map(|v| v.into()).collect();
         v.remove(0);
+        if let Some(help) = requested_help::<Self>(&v) {
+            return Err(Error::HelpRequested { help });
+        }
         Self::parse_vec(v)
     }
     /// For implementation, but not for using this library.
@@ -101,11 +503,91 @@ pub trait AutoArgs: Sized {
     /// error message indicating what went wrong.  The `prefix` is
     /// a string that should be inserted prior to a flag name.
     fn parse_internal(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error>;
+    /// For implementation, but not for using this library.
+    ///
+    /// Like `parse_internal`, but reporting every error rather than
+    /// stopping at the first.  The `Vec` of errors is never empty.
+    fn parse_internal_all(key: &str, args: &mut Vec<OsString>) -> Result<Self, Vec<Error>> {
+        Self::parse_internal(key, args).map_err(|e| vec![e])
+    }
+    /// For implementation, but not for using this library.
+    ///
+    /// Like `parse_internal`, but parsing only one occurrence of a
+    /// flag that takes a single value, and leaving any others in
+    /// `args`.  This is how a `Vec` is parsed.
+    fn parse_once(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
+        Self::parse_internal(key, args)
+    }
     /// Indicates whether this type requires any input.
     ///
     /// This is false if the data may be processed with no input, true
     /// otherwise.
     const REQUIRES_INPUT: bool;
+    /// Indicates whether the flag for this type is followed by a value.
+    ///
+    /// This is only false for types like `bool` where the flag alone
+    /// is the input.
+    const TAKES_VALUE: bool = true;
+    /// For implementation, but not for using this library.
+    ///
+    /// Whether `from_args` handles a hidden `--generate-completions
+    /// SHELL` flag and the `COMPLETE` environment variable, as is set
+    /// by `#[auto_args(completions)]`.
+    const COMPLETIONS: bool = false;
+    /// For implementation, but not for using this library.
+    ///
+    /// Whether `from_args` handles a hidden `--generate-man` flag, as
+    /// is set by `#[auto_args(man)]`.
+    const MAN: bool = false;
+    /// For implementation, but not for using this library.
+    ///
+    /// Whether `from_args` handles a hidden `--help-json` flag, as is
+    /// set by `#[auto_args(help_json)]`.
+    const HELP_JSON: bool = false;
+    /// For implementation, but not for using this library.
+    ///
+    /// The name of the program to show in place of the one it was run
+    /// by, as is set by `#[auto_args(name = "...")]`.
+    const NAME: Option<&'static str> = None;
+    /// For implementation, but not for using this library.
+    ///
+    /// Text to show at the end of the help, as is set by
+    /// `#[auto_args(after_help = "...")]`.
+    const AFTER_HELP: &'static str = "";
+    /// For implementation, but not for using this library.
+    ///
+    /// Return the short flags understood by this type, each given as
+    /// the single character, the long flag it stands for, and whether
+    /// that flag takes a value.
+    fn short_flags(_key: &str) -> Vec<(char, String, bool)> {
+        Vec::new()
+    }
+    /// For implementation, but not for using this library.
+    ///
+    /// Return every flag understood by this type, which we use to
+    /// suggest a flag when the user gives one we do not know.
+    fn flag_names(key: &str) -> Vec<String> {
+        if key.is_empty() {
+            Vec::new()
+        } else {
+            vec![key.to_string()]
+        }
+    }
+    /// For implementation, but not for using this library.
+    ///
+    /// Return the subcommands understood by this type, each given as
+    /// the command and its doc comment.
+    fn subcommands() -> Vec<(String, String)> {
+        Vec::new()
+    }
+    /// Describe the flags and arguments of this type, for building
+    /// tools.  The `key` is as for `parse_internal`, and is `""` for
+    /// the whole command line.
+    fn spec(key: &str) -> ArgSpec {
+        let mut spec = ArgSpec::value(key, Self::tiny_help_message(""));
+        spec.optional = !Self::REQUIRES_INPUT;
+        spec
+    }
     /// Return a tiny  help message.
     fn tiny_help_message(key: &str) -> String;
     /// Return a help message.
@@ -118,129 +600,1256 @@ pub trait AutoArgs: Sized {
             "USAGE:
   {} {}
 
-For more information try --help",
-            std::env::args_os()
-                .next()
-                .unwrap()
-                .to_string_lossy()
-                .rsplit("/")
-                .next()
-                .unwrap()
-                .to_string(),
-            Self::tiny_help_message("")
+{}For more information try --help",
+            program_name::<Self>(),
+            Self::tiny_help_message(""),
+            commands_message(&Self::subcommands())
         )
     }
-    /// Help text for the actual command
+    /// Help text for the actual command, as shown for `--help`, with
+    /// the whole doc comment of each flag.
+    ///
+    /// This starts with the doc comment of the type, and ends with any
+    /// `#[auto_args(after_help = "...")]`.
     fn help() -> String {
+        help_text::<Self>(true)
+    }
+    /// Help text for the actual command, as shown for `-h`, with only
+    /// the first line of the doc comment of each flag.
+    fn short_help() -> String {
+        help_text::<Self>(false)
+    }
+    /// A description of the whole command line as JSON, for tools
+    /// that build a user interface from it.
+    ///
+    /// This is an object with the keys `version` (which is
+    /// [`HELP_JSON_VERSION`]), `program`, `usage` and `spec`, which is
+    /// given by [`ArgSpec::to_json`].
+    fn help_json() -> String {
         format!(
-            "USAGE:
-  {} {}
+            "{{\"version\":{},\"program\":{},\"usage\":{},\"spec\":{}}}",
+            HELP_JSON_VERSION,
+            json_string(&program_name::<Self>()),
+            json_string(Self::tiny_help_message("").trim()),
+            Self::spec("").to_json()
+        )
+    }
+}
 
-{}
+/// An argument as normalized for parsing, with where it came from in
+/// `argv`.  Only a flag that has been joined with its value, as in
+/// `--flag=value`, has its value somewhere other than `at`.
+struct Located {
+    arg: OsString,
+    at: usize,
+    value_at: usize,
+}
+
+/// The arguments `args`, following the program name in `argv`.
+fn located(args: Vec<OsString>) -> Vec<Located> {
+    args.into_iter()
+        .enumerate()
+        .map(|(i, arg)| Located {
+            arg,
+            at: i + 1,
+            value_at: i + 1,
+        })
+        .collect()
+}
+
+/// Rewrite any single-dash short flags into the long flags they stand
+/// for, so that the rest of the parsing need only know long flags.
+///
+/// Boolean short flags may be clustered as in `-vq`, and the value of
+/// a short flag may be attached as in `-N100`.  The argument after a
+/// flag in `values`, or after a short flag that takes a value, is that
+/// value, and is left alone even if it looks like a short flag.  Each
+/// long flag is located at the cluster it came from.
+fn expand_short_flags(
+    shorts: &[(char, String, bool)],
+    values: &[String],
+    args: Vec<Located>,
+) -> Result<Vec<Located>, Error> {
+    if shorts.is_empty() {
+        return Ok(args);
+    }
+    let mut out = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg.arg == "--" {
+            out.push(arg);
+            out.extend(args);
+            break;
+        }
+        if values.iter().any(|v| arg.arg == v.as_str()) {
+            out.push(arg);
+            out.extend(args.next());
+            continue;
+        }
+        let cluster = match arg.arg.to_str() {
+            Some(s) if s.starts_with('-') && !s.starts_with("--") => s[1..].to_string(),
+            _ => {
+                out.push(arg);
+                continue;
+            }
+        };
+        if !shorts.iter().any(|(c, _, _)| cluster.starts_with(*c)) {
+            // This is not a short flag we know, e.g. a negative number.
+            out.push(arg);
+            continue;
+        }
+        let long = |flag: String| Located {
+            arg: flag.into(),
+            at: arg.at,
+            value_at: arg.at,
+        };
+        for (i, c) in cluster.char_indices() {
+            match shorts.iter().find(|(s, _, _)| *s == c) {
+                Some((_, flag, true)) => {
+                    let value = &cluster[i + c.len_utf8()..];
+                    let value = value.strip_prefix('=').unwrap_or(value);
+                    if value.is_empty() {
+                        out.push(long(flag.clone()));
+                        out.extend(args.next());
+                    } else {
+                        out.push(long(format!("{}={}", flag, value)));
+                    }
+                    break;
+                }
+                Some((_, flag, false)) => {
+                    out.push(long(flag.clone()));
+                }
+                None => {
+                    return Err(Error::UnexpectedOption {
+                        option: format!("-{}", c),
+                        index: Some(arg.at),
+                        suggestion: None,
+                    });
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Panic if two fields of `spec` are given the same flag, which is a
+/// mistake in the type rather than in the arguments.
+fn check_flags(spec: &ArgSpec) {
+    if let Err(flag) = distinct_flags(spec) {
+        panic!("the flag `{}` is given by more than one field", flag);
+    }
+}
+
+/// The flags, long and short, given by `spec`, or else a flag given
+/// by two of its fields, which could never be told apart.  The
+/// alternatives of an enum may share flags, as only one of them is
+/// parsed, and the variants of a subcommand are parsed on their own.
+fn distinct_flags(spec: &ArgSpec) -> Result<Vec<String>, String> {
+    match spec.kind {
+        ArgKind::Switch | ArgKind::Value { .. } => {
+            if spec.positional || spec.name.is_empty() {
+                Ok(Vec::new())
+            } else {
+                let short = spec.short.map(|c| format!("-{}", c));
+                Ok(std::iter::once(spec.name.clone()).chain(short).collect())
+            }
+        }
+        ArgKind::Group(ref specs) => {
+            let mut flags: Vec<String> = Vec::new();
+            for s in specs {
+                for flag in distinct_flags(s)? {
+                    if flags.contains(&flag) {
+                        return Err(flag);
+                    }
+                    flags.push(flag);
+                }
+            }
+            Ok(flags)
+        }
+        ArgKind::Alternatives(ref specs) => {
+            let mut flags: Vec<String> = Vec::new();
+            for s in specs {
+                for flag in distinct_flags(s)? {
+                    if !flags.contains(&flag) {
+                        flags.push(flag);
+                    }
+                }
+            }
+            if spec.positional {
+                flags.clear();
+            }
+            Ok(flags)
+        }
+    }
+}
+
+/// Join each flag in `values` with the argument after it, as in
+/// `--flag=value`, so that no positional argument or subcommand is
+/// taken from the value of a flag, wherever in the type it is parsed.
+///
+/// A value that is not UTF-8 is left on its own, since the joined
+/// form is split as a string.
+fn join_values(values: &[String], args: Vec<Located>) -> Vec<Located> {
+    let mut out = Vec::with_capacity(args.len());
+    let mut args = args.into_iter().peekable();
+    while let Some(arg) = args.next() {
+        if arg.arg == "--" {
+            out.push(arg);
+            out.extend(args);
+            break;
+        }
+        let value = match args.peek().and_then(|v| v.arg.to_str()) {
+            Some(value) if values.iter().any(|v| arg.arg == v.as_str()) => value.to_string(),
+            _ => {
+                out.push(arg);
+                continue;
+            }
+        };
+        let value_at = args.next().map_or(arg.at, |v| v.at);
+        out.push(Located {
+            arg: format!("{}={}", arg.arg.to_string_lossy(), value).into(),
+            at: arg.at,
+            value_at,
+        });
+    }
+    out
+}
+
+/// Where in `argv` each of the arguments `left` over from parsing
+/// `args` came from.  Parsing only removes arguments, so `left` is
+/// matched against `args` from the end, since positional arguments are
+/// taken from the front.
+fn leftover_positions(args: &[Located], left: &[OsString]) -> Vec<usize> {
+    let mut args = args.iter().rev();
+    let mut at: Vec<usize> = left
+        .iter()
+        .rev()
+        .filter_map(|l| args.find(|a| a.arg == *l).map(|a| a.at))
+        .collect();
+    at.reverse();
+    at
+}
+
+/// Add to `flags` every flag in `spec` that is followed by a value.
+fn value_flags(spec: &ArgSpec, flags: &mut Vec<String>) {
+    match spec.kind {
+        ArgKind::Value { .. } if !spec.positional && !spec.name.is_empty() => {
+            flags.push(spec.name.clone());
+        }
+        ArgKind::Group(ref specs) | ArgKind::Alternatives(ref specs) => {
+            for s in specs {
+                value_flags(s, flags);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// For implementation, but not for using this library.
+///
+/// Parse the flag `key`, falling back on the environment variable
+/// `var` when the flag is not given on the command line.
+#[doc(hidden)]
+pub fn parse_with_env<T: AutoArgs>(
+    key: &str,
+    var: &'static str,
+    args: &mut Vec<OsString>,
+) -> Result<T, Error> {
+    let eqthing = format!("{}=", key);
+    let given = args[..end_of_flags(args)]
+        .iter()
+        .any(|v| v == key || v.to_string_lossy().starts_with(&eqthing));
+    let value = match std::env::var_os(var) {
+        Some(value) if !given => value,
+        _ => return T::parse_internal(key, args),
+    };
+    let mut env_args = vec![OsString::from(key)];
+    if T::TAKES_VALUE {
+        env_args.push(value);
+    } else {
+        match value.to_string_lossy().to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => (),
+            "" | "0" | "false" | "no" | "off" => {
+                env_args.clear();
+            }
+            _ => {
+                return Err(Error::OptionValueParsingFailed {
+                    flag: key.to_string(),
+                    value: value.to_string_lossy().into_owned(),
+                    expected: "(true|false)".to_string(),
+                    index: None,
+                    env: Some(var),
+                    source: None,
+                });
+            }
+        }
+    }
+    T::parse_internal(key, &mut env_args).map_err(|mut e| {
+        if let Error::OptionValueParsingFailed { ref mut env, .. } | Error::InvalidUTF8 { ref mut env, .. } =
+            e
+        {
+            *env = Some(var);
+        }
+        e
+    })
+}
+
+/// The position in `args` of the first of `flags` to be given, and
+/// which flag it was.
+fn find_flag<'a>(args: &[OsString], flags: &'a [String]) -> Option<(usize, &'a String)> {
+    let end = end_of_flags(args);
+    args[..end].iter().enumerate().find_map(|(i, arg)| {
+        let arg = arg.to_string_lossy();
+        flags
+            .iter()
+            .filter(|f| !f.is_empty())
+            .find(|f| arg == f.as_str() || arg.starts_with(&format!("{}=", f)))
+            .map(|f| (i, f))
+    })
+}
+
+/// An error if the flag `key` remains in `args` after one occurrence
+/// of it has been parsed.  The other occurrences are removed along
+/// with their values, so that they are not also reported as unexpected.
+fn not_repeated(key: &str, args: &mut Vec<OsString>) -> Result<(), Error> {
+    let mut repeated = false;
+    while let Some((i, _)) = find_flag(args, &[key.to_string()]) {
+        if args[i] == key && i + 1 < args.len() && args[i + 1] != "--" {
+            args.remove(i + 1);
+        }
+        args.remove(i);
+        repeated = true;
+    }
+    if repeated {
+        Err(Error::RepeatedOption {
+            flag: key.to_string(),
+            index: None,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// For implementation, but not for using this library.
+///
+/// Parse the flag `key` with `parse`, using only its last occurrence
+/// if it is given more than once.
+#[doc(hidden)]
+pub fn parse_last<T: AutoArgs>(
+    key: &str,
+    args: &mut Vec<OsString>,
+    parse: impl Fn(&mut Vec<OsString>) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let orig_args = args.clone();
+    match parse(args) {
+        Err(Error::RepeatedOption { .. }) => {
+            *args = orig_args;
+            let eqthing = format!("{}=", key);
+            let mut given: Vec<usize> = (0..end_of_flags(args))
+                .filter(|&i| args[i] == key || args[i].to_string_lossy().starts_with(&eqthing))
+                .collect();
+            given.pop();
+            for &i in given.iter().rev() {
+                if T::TAKES_VALUE && args[i] == key && i + 1 < args.len() {
+                    args.remove(i + 1);
+                }
+                args.remove(i);
+            }
+            parse(args)
+        }
+        result => result,
+    }
+}
+
+/// For implementation, but not for using this library.
+///
+/// Using `TakesValue::<T>::CHECK` fails to compile unless the flag of
+/// `T` is followed by a value, since a `default` makes no sense for a
+/// type such as `bool`.
+#[doc(hidden)]
+pub struct TakesValue<T>(std::marker::PhantomData<T>);
 
-For more information try --help",
-            std::env::args_os()
-                .next()
-                .unwrap()
+impl<T: AutoArgs> TakesValue<T> {
+    #[doc(hidden)]
+    pub const CHECK: () = assert!(
+        T::TAKES_VALUE,
+        "`default` cannot be given for a type that takes no value, such as `bool`"
+    );
+}
+
+/// For implementation, but not for using this library.
+///
+/// Choose the variant of an enum whose flags appear in `args`, given
+/// the flags of each variant.  It is an error for flags of two
+/// variants to both appear, and `None` if no variant's flags do.  A
+/// flag shared by several variants does not choose between them.
+#[doc(hidden)]
+pub fn choose_variant(args: &[OsString], variant_flags: &[Vec<String>]) -> Result<Option<usize>, Error> {
+    let shared = |variant: usize, flag: &String| {
+        variant_flags
+            .iter()
+            .enumerate()
+            .any(|(v, flags)| v != variant && flags.contains(flag))
+    };
+    let mut found: Vec<(usize, usize, String)> = Vec::new();
+    for (variant, flags) in variant_flags.iter().enumerate() {
+        let own: Vec<String> = flags.iter().filter(|f| !shared(variant, f)).cloned().collect();
+        if let Some((i, f)) = find_flag(args, &own) {
+            found.push((i, variant, f.clone()));
+        }
+    }
+    found.sort();
+    match found[..] {
+        [] => Ok(None),
+        [(_, variant, _)] => Ok(Some(variant)),
+        [(_, _, ref first), (_, _, ref second), ..] => Err(Error::ConflictingOptions {
+            option: second.clone(),
+            conflicts_with: first.clone(),
+            index: None,
+        }),
+    }
+}
+
+/// For implementation, but not for using this library.
+///
+/// Remove the flags of every variant from `args`, once they are found
+/// to conflict, so that the conflict is the only error they give.
+#[doc(hidden)]
+pub fn remove_variant_flags(args: &mut Vec<OsString>, variant_flags: &[Vec<String>]) {
+    let flags = variant_flags.concat();
+    while let Some((i, _)) = find_flag(args, &flags) {
+        args.remove(i);
+    }
+}
+
+/// The errors for any arguments that nothing wanted, which came from
+/// the positions `at` in `argv`.  Each is reported as it was given in
+/// `argv`, rather than as it was rewritten for parsing.
+fn leftover_errors(args: &[OsString], at: &[usize], argv: &[OsString], flags: &[String]) -> Vec<Error> {
+    let end = end_of_flags(args);
+    args.iter()
+        .enumerate()
+        .filter(|&(i, _)| i != end)
+        .map(|(i, arg)| {
+            let index = at.get(i).copied();
+            let arg = index
+                .and_then(|at| argv.get(at - 1))
+                .unwrap_or(arg)
                 .to_string_lossy()
-                .rsplit("/")
-                .next()
-                .unwrap()
-                .to_string(),
-            Self::tiny_help_message(""),
-            align_tabs(&Self::help_message("", ""))
-        )
+                .into_owned();
+            if i < end && arg.starts_with('-') {
+                Error::UnexpectedOption {
+                    suggestion: suggest_flag(&arg, flags),
+                    option: arg,
+                    index,
+                }
+            } else {
+                Error::UnexpectedArgument { value: arg, index }
+            }
+        })
+        .collect()
+}
+
+/// The number of single-character edits needed to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = diagonal + if ca == *cb { 0 } else { 1 };
+            diagonal = row[j + 1];
+            row[j + 1] = std::cmp::min(substitute, std::cmp::min(row[j], row[j + 1]) + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// The flag among `flags` that `option` was most likely meant to be,
+/// if any is close enough.
+fn suggest_flag(option: &str, flags: &[String]) -> Option<String> {
+    let option = option.split('=').next().unwrap_or(option);
+    flags
+        .iter()
+        .map(|f| (edit_distance(option, f), f))
+        .filter(|(d, f)| *d > 0 && *d <= std::cmp::max(1, f.len() / 3))
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, f)| f.clone())
+}
+
+/// The number of arguments prior to any `--`, after which nothing
+/// is treated as a flag.
+fn end_of_flags(args: &[OsString]) -> usize {
+    args.iter().position(|v| v == "--").unwrap_or(args.len())
+}
+
+/// Whether `arg`, coming before any `--`, is a positional argument
+/// rather than a flag.  Besides anything not starting with a dash,
+/// this is `-` alone (which often means stdin) and negative numbers.
+fn is_positional(arg: &OsString) -> bool {
+    let arg = arg.to_string_lossy();
+    !arg.starts_with('-') || arg == "-" || arg.parse::<f64>().is_ok()
+}
+
+/// For implementation, but not for using this library.
+///
+/// Parse a positional argument called `name` (as shown in the usage)
+/// from whatever arguments are not flags.  Anything following a `--`
+/// is taken to be positional.
+#[doc(hidden)]
+pub fn parse_positional<T: AutoArgs>(name: &str, args: &mut Vec<OsString>) -> Result<T, Error> {
+    let end = end_of_flags(args);
+    let positions: Vec<usize> = (0..args.len())
+        .filter(|&i| i > end || (i < end && is_positional(&args[i])))
+        .collect();
+    let mut values: Vec<OsString> = positions.iter().map(|&i| args[i].clone()).collect();
+    let available = values.len();
+    let result = T::parse_internal("", &mut values);
+    for &i in positions[..available - values.len()].iter().rev() {
+        args.remove(i);
+    }
+    result.map_err(|e| match e {
+        Error::MissingOption { .. } | Error::OptionWithoutAValue { .. } => Error::MissingArgument {
+            name: name.to_string(),
+        },
+        Error::OptionValueParsingFailed {
+            value,
+            expected,
+            index,
+            env,
+            source,
+            ..
+        } => Error::OptionValueParsingFailed {
+            flag: name.to_string(),
+            value,
+            expected,
+            index,
+            env,
+            source,
+        },
+        Error::InvalidUTF8 {
+            value, index, env, ..
+        } => Error::InvalidUTF8 {
+            flag: name.to_string(),
+            value,
+            index,
+            env,
+        },
+        e => e,
+    })
+}
+
+/// For implementation, but not for using this library.
+///
+/// Take all of the arguments after the first `--`, exactly as given.
+#[doc(hidden)]
+pub fn parse_trailing(args: &mut Vec<OsString>) -> Vec<OsString> {
+    match args.iter().position(|v| v == "--") {
+        Some(i) => {
+            let trailing = args.split_off(i + 1);
+            args.pop();
+            trailing
+        }
+        None => Vec::new(),
     }
 }
 
+/// For implementation, but not for using this library.
+///
+/// The usage for a positional argument called `name`, which is
+/// optional if it need not be given.
+#[doc(hidden)]
+pub fn positional_usage<T: AutoArgs>(name: &str, optional: bool) -> String {
+    if T::tiny_help_message("").ends_with("...") {
+        format!("{}...", name)
+    } else if optional || !T::REQUIRES_INPUT {
+        format!("[{}]", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// The underlying cause of an [`Error`].
+pub type Source = std::sync::Arc<dyn std::error::Error + Send + Sync>;
+
 /// A list of possible errors.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// Where it is known, the `index` of an error is the position in
+/// `argv` of the argument at fault, counting the program name as zero.
+#[derive(Clone, Debug)]
 pub enum Error {
-    /// An error from pico-args.
-    OptionValueParsingFailed(String, String),
+    /// A value that could not be converted into the type of its flag.
+    OptionValueParsingFailed {
+        /// The flag, or the name of the positional argument.
+        flag: String,
+        /// The value as given.
+        value: String,
+        /// The kind of value expected, as shown in the usage.
+        expected: String,
+        /// Where the value is in `argv`.
+        index: Option<usize>,
+        /// The environment variable the value came from, if it was not
+        /// given on the command line.
+        env: Option<&'static str>,
+        /// Why the value could not be converted.
+        source: Option<Source>,
+    },
 
-    /// A missing value from an option.
-    InvalidUTF8(String),
+    /// A value that is not valid UTF-8.
+    InvalidUTF8 {
+        /// The flag, or the name of the positional argument.
+        flag: String,
+        /// The value as given.
+        value: OsString,
+        /// Where the value is in `argv`.
+        index: Option<usize>,
+        /// The environment variable the value came from, if it was not
+        /// given on the command line.
+        env: Option<&'static str>,
+    },
 
     /// A missing value from an option.
-    OptionWithoutAValue(String),
+    OptionWithoutAValue {
+        /// The flag.
+        flag: String,
+        /// The kind of value expected, as shown in the usage.
+        expected: String,
+        /// Where the flag is in `argv`.
+        index: Option<usize>,
+    },
 
     /// A missing required flag.
-    MissingOption(String),
+    MissingOption {
+        /// The flag.
+        flag: String,
+        /// The kind of value expected, as shown in the usage.
+        expected: String,
+    },
+
+    /// Some but not all of the required flags of an optional group.
+    IncompleteOptions {
+        /// The flags of the group that were given.
+        given: Vec<String>,
+        /// The required flags of the group that were not.
+        missing: Vec<String>,
+    },
 
     /// An unexpected option.
-    UnexpectedOption(String),
+    UnexpectedOption {
+        /// The option as given.
+        option: String,
+        /// Where the option is in `argv`.
+        index: Option<usize>,
+        /// A similar flag that the user may have meant.
+        suggestion: Option<String>,
+    },
+
+    /// A flag that takes a single value, given more than once.
+    RepeatedOption {
+        /// The flag.
+        flag: String,
+        /// Where the second occurrence of the flag is in `argv`.
+        index: Option<usize>,
+    },
+
+    /// Options from two alternatives of an enum.
+    ConflictingOptions {
+        /// The later of the two options.
+        option: String,
+        /// The option given first.
+        conflicts_with: String,
+        /// Where the later option is in `argv`.
+        index: Option<usize>,
+    },
+
+    /// A missing required positional argument.
+    MissingArgument {
+        /// The name of the argument, as shown in the usage.
+        name: String,
+    },
+
+    /// An unexpected positional argument.
+    UnexpectedArgument {
+        /// The argument as given.
+        value: String,
+        /// Where the argument is in `argv`.
+        index: Option<usize>,
+    },
+
+    /// The user asked for help with `--help`.
+    HelpRequested {
+        /// The help message.
+        help: String,
+    },
+
+    /// Any other error, for manual implementations of `AutoArgs`.
+    Custom {
+        /// What went wrong.
+        message: String,
+        /// The underlying cause, if any.
+        source: Option<Source>,
+    },
+}
+
+impl Error {
+    /// An error with the given message, for manual implementations of
+    /// `AutoArgs`.
+    pub fn custom(message: impl Into<String>) -> Self {
+        Error::Custom {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// The name of this kind of error, such as `"missing_option"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::OptionValueParsingFailed { .. } => "option_value_parsing_failed",
+            Error::InvalidUTF8 { .. } => "invalid_utf8",
+            Error::OptionWithoutAValue { .. } => "option_without_a_value",
+            Error::MissingOption { .. } => "missing_option",
+            Error::RepeatedOption { .. } => "repeated_option",
+            Error::IncompleteOptions { .. } => "incomplete_options",
+            Error::UnexpectedOption { .. } => "unexpected_option",
+            Error::ConflictingOptions { .. } => "conflicting_options",
+            Error::MissingArgument { .. } => "missing_argument",
+            Error::UnexpectedArgument { .. } => "unexpected_argument",
+            Error::HelpRequested { .. } => "help_requested",
+            Error::Custom { .. } => "custom",
+        }
+    }
+
+    /// This error as a JSON object.
+    ///
+    /// Every object has the keys `kind`, `message`, `flag`, `value`,
+    /// `expected`, `index` and `suggestions`, which are `null` (or an
+    /// empty array) where they do not apply.  Conflicting and
+    /// incomplete options also give `conflicts_with`, or `given` and
+    /// `missing`, and a value from an environment variable gives `env`.
+    pub fn to_json(&self) -> String {
+        let string = |s: &str| json_string(s);
+        let list = |v: &[String]| {
+            let v: Vec<String> = v.iter().map(|s| json_string(s)).collect();
+            format!("[{}]", v.join(","))
+        };
+        let null = || "null".to_string();
+        let (mut flag, mut value, mut expected, mut index) = (null(), null(), null(), null());
+        let mut suggestions = "[]".to_string();
+        let mut extra = String::new();
+        match self {
+            Error::OptionValueParsingFailed {
+                flag: f,
+                value: v,
+                expected: e,
+                index: i,
+                env,
+                ..
+            } => {
+                flag = string(f);
+                value = string(v);
+                expected = string(e);
+                index = i.map_or_else(null, |i| i.to_string());
+                if let Some(env) = env {
+                    extra = format!(",\"env\":{}", string(env));
+                }
+            }
+            Error::InvalidUTF8 {
+                flag: f,
+                value: v,
+                index: i,
+                env,
+            } => {
+                flag = string(f);
+                value = string(&v.to_string_lossy());
+                index = i.map_or_else(null, |i| i.to_string());
+                if let Some(env) = env {
+                    extra = format!(",\"env\":{}", string(env));
+                }
+            }
+            Error::OptionWithoutAValue {
+                flag: f,
+                expected: e,
+                index: i,
+            } => {
+                flag = string(f);
+                expected = string(e);
+                index = i.map_or_else(null, |i| i.to_string());
+            }
+            Error::MissingOption {
+                flag: f,
+                expected: e,
+            } => {
+                flag = string(f);
+                expected = string(e);
+            }
+            Error::RepeatedOption { flag: f, index: i } => {
+                flag = string(f);
+                index = i.map_or_else(null, |i| i.to_string());
+            }
+            Error::IncompleteOptions { given, missing } => {
+                extra = format!(",\"given\":{},\"missing\":{}", list(given), list(missing));
+            }
+            Error::UnexpectedOption {
+                option,
+                index: i,
+                suggestion,
+            } => {
+                flag = string(option);
+                index = i.map_or_else(null, |i| i.to_string());
+                suggestions = list(suggestion.as_slice());
+            }
+            Error::ConflictingOptions {
+                option,
+                conflicts_with,
+                index: i,
+            } => {
+                flag = string(option);
+                index = i.map_or_else(null, |i| i.to_string());
+                extra = format!(",\"conflicts_with\":{}", string(conflicts_with));
+            }
+            Error::MissingArgument { name } => {
+                flag = string(name);
+            }
+            Error::UnexpectedArgument { value: v, index: i } => {
+                value = string(v);
+                index = i.map_or_else(null, |i| i.to_string());
+            }
+            Error::HelpRequested { .. } | Error::Custom { .. } => (),
+        }
+        format!(
+            "{{\"kind\":{},\"message\":{},\"flag\":{},\"value\":{},\"expected\":{},\"index\":{},\"suggestions\":{}{}}}",
+            string(self.kind()),
+            string(&self.to_string()),
+            flag,
+            value,
+            expected,
+            index,
+            suggestions,
+            extra
+        )
+    }
+
+    /// Use `expected` as the kind of value expected by this error.
+    fn expecting(mut self, kind: &str) -> Self {
+        match self {
+            Error::OptionValueParsingFailed {
+                ref mut expected, ..
+            }
+            | Error::OptionWithoutAValue {
+                ref mut expected, ..
+            }
+            | Error::MissingOption {
+                ref mut expected, ..
+            } => {
+                *expected = kind.to_string();
+            }
+            _ => (),
+        }
+        self
+    }
+
+    /// Find where in `argv` this error happened, if we do not already
+    /// know, from the normalized arguments `args`.
+    fn locate(mut self, args: &[Located]) -> Self {
+        let position = |s: &str| args.iter().find(|a| a.arg == s).map(|a| a.at);
+        let given = |flag: &str| {
+            let eqthing = format!("{}=", flag);
+            args.iter()
+                .filter(|a| a.arg == flag || a.arg.to_string_lossy().starts_with(&eqthing))
+                .map(|a| a.at)
+                .collect::<Vec<_>>()
+        };
+        match self {
+            Error::OptionValueParsingFailed {
+                ref flag,
+                ref value,
+                ref mut index,
+                ..
+            } if index.is_none() => {
+                let eqthing = format!("{}={}", flag, value);
+                *index = args
+                    .iter()
+                    .find(|a| a.arg == eqthing.as_str())
+                    .map(|a| a.value_at)
+                    .or_else(|| {
+                        args.windows(2)
+                            .find(|w| w[0].arg == flag.as_str() && w[1].arg == value.as_str())
+                            .map(|w| w[1].at)
+                    })
+                    .or_else(|| position(value));
+            }
+            Error::RepeatedOption {
+                ref flag,
+                ref mut index,
+            } if index.is_none() => {
+                *index = given(flag).get(1).copied();
+            }
+            Error::ConflictingOptions {
+                option: ref flag,
+                ref mut index,
+                ..
+            } if index.is_none() => {
+                *index = given(flag).first().copied();
+            }
+            Error::InvalidUTF8 {
+                ref value,
+                ref mut index,
+                ..
+            } if index.is_none() => {
+                *index = args.iter().find(|a| a.arg == *value).map(|a| a.at);
+            }
+            Error::OptionWithoutAValue {
+                flag: ref s,
+                ref mut index,
+                ..
+            }
+            | Error::UnexpectedOption {
+                option: ref s,
+                ref mut index,
+                ..
+            }
+            | Error::UnexpectedArgument {
+                value: ref s,
+                ref mut index,
+            } if index.is_none() => {
+                *index = position(s);
+            }
+            _ => (),
+        }
+        self
+    }
+}
+
+/// Turn a message into a [`Source`], for errors that give us no more.
+#[cfg(feature = "meval")]
+fn message_source(message: String) -> Source {
+    Box::<dyn std::error::Error + Send + Sync>::from(message).into()
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        // A source cannot be compared directly, so we compare what
+        // it says.
+        let same = |a: &Option<Source>, b: &Option<Source>| {
+            a.as_ref().map(|e| e.to_string()) == b.as_ref().map(|e| e.to_string())
+        };
+        match (self, other) {
+            (
+                Error::OptionValueParsingFailed {
+                    flag,
+                    value,
+                    expected,
+                    index,
+                    env,
+                    source,
+                },
+                Error::OptionValueParsingFailed {
+                    flag: f,
+                    value: v,
+                    expected: e,
+                    index: i,
+                    env: n,
+                    source: s,
+                },
+            ) => {
+                flag == f
+                    && value == v
+                    && expected == e
+                    && index == i
+                    && env == n
+                    && same(source, s)
+            }
+            (
+                Error::InvalidUTF8 {
+                    flag,
+                    value,
+                    index,
+                    env,
+                },
+                Error::InvalidUTF8 {
+                    flag: f,
+                    value: v,
+                    index: i,
+                    env: n,
+                },
+            ) => flag == f && value == v && index == i && env == n,
+            (
+                Error::OptionWithoutAValue {
+                    flag,
+                    expected,
+                    index,
+                },
+                Error::OptionWithoutAValue {
+                    flag: f,
+                    expected: e,
+                    index: i,
+                },
+            ) => flag == f && expected == e && index == i,
+            (
+                Error::MissingOption { flag, expected },
+                Error::MissingOption {
+                    flag: f,
+                    expected: e,
+                },
+            ) => flag == f && expected == e,
+            (
+                Error::IncompleteOptions { given, missing },
+                Error::IncompleteOptions {
+                    given: g,
+                    missing: m,
+                },
+            ) => given == g && missing == m,
+            (
+                Error::UnexpectedOption {
+                    option,
+                    index,
+                    suggestion,
+                },
+                Error::UnexpectedOption {
+                    option: o,
+                    index: i,
+                    suggestion: s,
+                },
+            ) => option == o && index == i && suggestion == s,
+            (
+                Error::RepeatedOption { flag, index },
+                Error::RepeatedOption { flag: f, index: i },
+            ) => flag == f && index == i,
+            (
+                Error::ConflictingOptions {
+                    option,
+                    conflicts_with,
+                    index,
+                },
+                Error::ConflictingOptions {
+                    option: o,
+                    conflicts_with: c,
+                    index: i,
+                },
+            ) => option == o && conflicts_with == c && index == i,
+            (Error::MissingArgument { name }, Error::MissingArgument { name: n }) => name == n,
+            (
+                Error::UnexpectedArgument { value, index },
+                Error::UnexpectedArgument { value: v, index: i },
+            ) => value == v && index == i,
+            (Error::HelpRequested { help }, Error::HelpRequested { help: h }) => help == h,
+            (
+                Error::Custom { message, source },
+                Error::Custom {
+                    message: m,
+                    source: s,
+                },
+            ) => message == m && same(source, s),
+            _ => false,
+        }
+    }
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let location = |f: &mut std::fmt::Formatter, index: &Option<usize>| match index {
+            Some(i) => write!(f, "\n  --> argument {}", i),
+            None => Ok(()),
+        };
+        let expecting = |f: &mut std::fmt::Formatter, expected: &str| {
+            if expected.is_empty() {
+                Ok(())
+            } else {
+                write!(f, "\n   = expected: {}", expected)
+            }
+        };
+        let from_env = |f: &mut std::fmt::Formatter, env: &Option<&str>| match env {
+            Some(env) => write!(f, " (from environment variable {})", env),
+            None => Ok(()),
+        };
         match self {
-            Error::OptionValueParsingFailed(key, e) => {
-                write!(f, "error parsing option '{}': {}", key, e)
+            Error::OptionValueParsingFailed {
+                flag,
+                value,
+                expected,
+                index,
+                env,
+                source,
+            } => {
+                write!(f, "invalid value `{}` for `{}`", value, flag)?;
+                from_env(f, env)?;
+                location(f, index)?;
+                expecting(f, expected)?;
+                if let Some(source) = source {
+                    write!(f, "\n   = note: {}", source)?;
+                }
+                Ok(())
             }
-            Error::MissingOption(key) => {
-                write!(f, "the required '{}' option is missing", key)
+            Error::InvalidUTF8 {
+                flag,
+                value,
+                index,
+                env,
+            } => {
+                write!(f, "invalid UTF-8 in {:?} for `{}`", value, flag)?;
+                from_env(f, env)?;
+                location(f, index)
             }
-            Error::InvalidUTF8(e) => {
-                write!(f, "invalid UTF-8: '{}'", e)
+            Error::OptionWithoutAValue {
+                flag,
+                expected,
+                index,
+            } => {
+                write!(f, "the option `{}` is missing a value", flag)?;
+                location(f, index)?;
+                expecting(f, expected)
             }
-            Error::OptionWithoutAValue(key) => {
-                write!(f, "the option '{}' is missing a value", key)
+            Error::MissingOption { flag, expected } => {
+                if flag.is_empty() {
+                    write!(f, "a required argument is missing")?;
+                    expecting(f, expected)
+                } else {
+                    write!(f, "the required option `{}` is missing", flag)?;
+                    if expected.is_empty() {
+                        write!(f, "\n   = help: try adding `{}`", flag)
+                    } else {
+                        write!(f, "\n   = help: try adding `{} {}`", flag, expected)
+                    }
+                }
+            }
+            Error::UnexpectedOption {
+                option,
+                index,
+                suggestion,
+            } => {
+                write!(f, "unexpected option `{}`", option)?;
+                location(f, index)?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, "\n   = help: did you mean `{}`?", suggestion)?;
+                }
+                Ok(())
+            }
+            Error::RepeatedOption { flag, index } => {
+                write!(f, "the option `{}` was given more than once", flag)?;
+                location(f, index)?;
+                write!(f, "\n   = help: give it only once")
+            }
+            Error::IncompleteOptions { given, missing } => {
+                let list = |flags: &[String]| format!("`{}`", flags.join("`, `"));
+                if missing.is_empty() {
+                    write!(f, "{} cannot be given alone", list(given))?;
+                } else {
+                    write!(
+                        f,
+                        "{} cannot be given without {}",
+                        list(given),
+                        list(missing)
+                    )?;
+                }
+                write!(f, "\n   = help: give all of these options, or none of them")
+            }
+            Error::ConflictingOptions {
+                option,
+                conflicts_with,
+                index,
+            } => {
+                write!(
+                    f,
+                    "the option `{}` cannot be used with `{}`",
+                    option, conflicts_with
+                )?;
+                location(f, index)?;
+                write!(f, "\n   = note: they choose different alternatives")
             }
-            Error::UnexpectedOption(o) => {
-                write!(f, "unexpected option: {}", o)
+            Error::MissingArgument { name } => {
+                write!(f, "the required argument `{}` is missing", name)
             }
+            Error::UnexpectedArgument { value, index } => {
+                write!(f, "unexpected argument `{}`", value)?;
+                location(f, index)
+            }
+            Error::HelpRequested { help } => write!(f, "{}", help),
+            Error::Custom { message, .. } => write!(f, "{}", message),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::OptionValueParsingFailed { source, .. } | Error::Custom { source, .. } => {
+                source.as_ref().map(|s| &**s as &(dyn std::error::Error + 'static))
+            }
+            _ => None,
+        }
+    }
+}
 
 macro_rules! impl_from_osstr {
     ($t:ty, $tyname:expr, $conv:expr) => {
         impl AutoArgs for $t {
             const REQUIRES_INPUT: bool = true;
             fn parse_internal(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
+                let value = Self::parse_once(key, args)?;
+                not_repeated(key, args)?;
+                Ok(value)
+            }
+            fn parse_once(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
                 let convert = $conv;
                 if key == "" {
                     if args.len() == 0 {
-                        Err(Error::MissingOption("".to_string()))
+                        Err(Error::MissingOption {
+                            flag: String::new(),
+                            expected: $tyname.to_string(),
+                        })
                     } else {
                         let arg = if args[0] == "--" {
                             if args.len() > 1 {
                                 args.remove(1)
                             } else {
-                                return Err(Error::OptionWithoutAValue("".to_string()));
+                                return Err(Error::OptionWithoutAValue {
+                                    flag: String::new(),
+                                    expected: $tyname.to_string(),
+                                    index: None,
+                                });
                             }
                         } else {
                             args.remove(0)
                         };
-                        convert(arg)
+                        convert(key, arg)
                     }
                 } else {
                     let eqthing = format!("{}=", key);
-                    if let Some(i) = args
+                    if let Some(i) = args[..end_of_flags(args)]
                         .iter()
                         .position(|v| v == key || v.to_string_lossy().starts_with(&eqthing))
                     {
-                        let thing = args
-                            .remove(i)
-                            .into_string()
-                            .map_err(|e| Error::InvalidUTF8(format!("{:?}", e)))?;
+                        let thing = args.remove(i).into_string().map_err(|value| Error::InvalidUTF8 {
+                            flag: key.to_string(),
+                            value,
+                            index: None,
+                            env: None,
+                        })?;
                         if thing == key {
                             if args.len() > i {
-                                convert(args.remove(i))
+                                convert(key, args.remove(i))
                             } else {
-                                Err(Error::OptionWithoutAValue(key.to_string()))
+                                Err(Error::OptionWithoutAValue {
+                                    flag: key.to_string(),
+                                    expected: $tyname.to_string(),
+                                    index: None,
+                                })
                             }
                         } else {
-                            convert(thing.split_at(eqthing.len()).1.into())
+                            convert(key, thing.split_at(eqthing.len()).1.into())
                         }
                     } else {
-                        Err(Error::MissingOption(key.to_string()))
+                        Err(Error::MissingOption {
+                            flag: key.to_string(),
+                            expected: $tyname.to_string(),
+                        })
                     }
                 }
             }
+            fn spec(key: &str) -> ArgSpec {
+                ArgSpec::value(key, $tyname)
+            }
             fn tiny_help_message(key: &str) -> String {
                 if key == "" {
                     "STRING".to_string()
@@ -255,11 +1864,11 @@ macro_rules! impl_from_osstr {
             fn parse_internal(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
                 let mut res: Self = Vec::new();
                 loop {
-                    match <$t>::parse_internal(key, args) {
+                    match <$t>::parse_once(key, args) {
                         Ok(the_arg) => {
                             res.push(the_arg);
                         }
-                        Err(Error::MissingOption(_)) => {
+                        Err(Error::MissingOption { .. }) => {
                             return Ok(res);
                         }
                         Err(e) => {
@@ -268,6 +1877,9 @@ macro_rules! impl_from_osstr {
                     }
                 }
             }
+            fn spec(key: &str) -> ArgSpec {
+                <$t>::spec(key).repeated()
+            }
             fn tiny_help_message(key: &str) -> String {
                 if key == "" {
                     format!("{}...", $tyname)
@@ -279,54 +1891,71 @@ macro_rules! impl_from_osstr {
     };
 }
 
-impl_from_osstr!(String, "STRING", |osstring: OsString| {
-    osstring
-        .into_string()
-        .map_err(|e| Error::InvalidUTF8(format!("{:?}", e)))
+impl_from_osstr!(String, "STRING", |key: &str, osstring: OsString| {
+    osstring.into_string().map_err(|value| Error::InvalidUTF8 {
+        flag: key.to_string(),
+        value,
+        index: None,
+        env: None,
+    })
 });
-impl_from_osstr!(PathBuf, "PATH", |osstring: OsString| {
+impl_from_osstr!(PathBuf, "PATH", |_key: &str, osstring: OsString| {
     Ok(osstring.into())
 });
 
 impl AutoArgs for bool {
     const REQUIRES_INPUT: bool = false;
+    const TAKES_VALUE: bool = false;
     fn parse_internal(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
-        if key == "" {
-            if args.len() == 0 {
-                Err(Error::OptionWithoutAValue("".to_string()))
+        if key.is_empty() {
+            if args.is_empty() || args[0] == "--" {
+                Err(Error::OptionWithoutAValue {
+                    flag: String::new(),
+                    expected: "(true|false)".to_string(),
+                    index: None,
+                })
             } else {
-                if args[0] == "--" {
-                    return Err(Error::OptionWithoutAValue("bool".to_string()));
-                }
                 let arg = args.remove(0);
                 if arg == "false" {
                     Ok(false)
                 } else if arg == "true" {
                     Ok(true)
                 } else {
-                    Err(Error::MissingOption("bool".to_string()))
+                    Err(Error::OptionValueParsingFailed {
+                        flag: String::new(),
+                        value: arg.to_string_lossy().into_owned(),
+                        expected: "(true|false)".to_string(),
+                        index: None,
+                        env: None,
+                        source: None,
+                    })
                 }
             }
         } else {
-            if args
-                .iter()
-                .filter(|v| v.to_string_lossy() == key)
-                .next()
-                .is_some()
-            {
-                *args = args
-                    .iter()
-                    .filter(|v| v.to_string_lossy() != key)
-                    .cloned()
-                    .collect();
+            let end = end_of_flags(args);
+            if args[..end].iter().any(|v| v.to_string_lossy() == key) {
+                let mut i = 0;
+                args.retain(|v| {
+                    i += 1;
+                    i > end || v.to_string_lossy() != key
+                });
                 Ok(true)
             } else {
                 Ok(false)
             }
         }
     }
+    fn spec(key: &str) -> ArgSpec {
+        if key.is_empty() {
+            ArgSpec::value(key, "(true|false)")
+        } else {
+            let mut spec = ArgSpec::new(key, ArgKind::Switch);
+            spec.optional = true;
+            spec
+        }
+    }
     fn tiny_help_message(key: &str) -> String {
-        if key == "" {
+        if key.is_empty() {
             "(true|false)".to_string()
         } else {
             format!("[{}]", key)
@@ -336,8 +1965,50 @@ impl AutoArgs for bool {
 
 impl<T: AutoArgs> AutoArgs for Option<T> {
     const REQUIRES_INPUT: bool = false;
+    const TAKES_VALUE: bool = T::TAKES_VALUE;
     fn parse_internal(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
-        Ok(T::parse_internal(key, args).ok())
+        let orig_args = args.clone();
+        match T::parse_internal(key, args) {
+            Ok(v) => Ok(Some(v)),
+            Err(Error::MissingOption { .. }) => {
+                // Only if none of its flags were given is this `None`,
+                // otherwise the user has given some but not all.
+                let given: Vec<String> = T::flag_names(key)
+                    .into_iter()
+                    .filter(|f| find_flag(&orig_args, std::slice::from_ref(f)).is_some())
+                    .collect();
+                if given.is_empty() {
+                    *args = orig_args;
+                    return Ok(None);
+                }
+                let missing = match T::parse_internal_all(key, &mut orig_args.clone()) {
+                    Ok(_) => Vec::new(),
+                    Err(errors) => errors
+                        .into_iter()
+                        .filter_map(|e| match e {
+                            Error::MissingOption { flag, .. } if !flag.is_empty() => Some(flag),
+                            _ => None,
+                        })
+                        .collect(),
+                };
+                Err(Error::IncompleteOptions { given, missing })
+            }
+            Err(e) => Err(e),
+        }
+    }
+    fn short_flags(key: &str) -> Vec<(char, String, bool)> {
+        T::short_flags(key)
+    }
+    fn flag_names(key: &str) -> Vec<String> {
+        T::flag_names(key)
+    }
+    fn subcommands() -> Vec<(String, String)> {
+        T::subcommands()
+    }
+    fn spec(key: &str) -> ArgSpec {
+        let mut spec = T::spec(key);
+        spec.optional = true;
+        spec
     }
     fn tiny_help_message(key: &str) -> String {
         format!("[{}]", T::tiny_help_message(key))
@@ -349,15 +2020,24 @@ macro_rules! impl_from {
         impl AutoArgs for $t {
             const REQUIRES_INPUT: bool = true;
             fn parse_internal(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
+                let value = Self::parse_once(key, args)?;
+                not_repeated(key, args)?;
+                Ok(value)
+            }
+            fn parse_once(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
                 use std::str::FromStr;
-                let the_arg = String::parse_internal(key, args)?;
+                let the_arg = String::parse_once(key, args).map_err(|e| e.expecting($tyname))?;
                 match Self::from_str(&the_arg) {
                     Ok(val) => Ok(val),
                     Err(e) => {
-                        let e = Err(Error::OptionValueParsingFailed(
-                            key.to_string(),
-                            e.to_string(),
-                        ));
+                        let e = Err(Error::OptionValueParsingFailed {
+                            flag: key.to_string(),
+                            value: the_arg.clone(),
+                            expected: $tyname.to_string(),
+                            index: None,
+                            env: None,
+                            source: Some(std::sync::Arc::new(e)),
+                        });
                         #[cfg(feature = "meval")]
                         let out = if let Ok(x) = meval::eval_str(&the_arg) {
                             if (x as $t) as f64 == x {
@@ -374,6 +2054,9 @@ macro_rules! impl_from {
                     }
                 }
             }
+            fn spec(key: &str) -> ArgSpec {
+                ArgSpec::value(key, $tyname)
+            }
             fn tiny_help_message(key: &str) -> String {
                 if key == "" {
                     $tyname.to_string()
@@ -388,11 +2071,11 @@ macro_rules! impl_from {
             fn parse_internal(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
                 let mut res: Self = Vec::new();
                 loop {
-                    match <$t>::parse_internal(key, args) {
+                    match <$t>::parse_once(key, args) {
                         Ok(val) => {
                             res.push(val);
                         }
-                        Err(Error::MissingOption(_)) => {
+                        Err(Error::MissingOption { .. }) => {
                             return Ok(res);
                         }
                         Err(e) => {
@@ -401,6 +2084,9 @@ macro_rules! impl_from {
                     }
                 }
             }
+            fn spec(key: &str) -> ArgSpec {
+                <$t>::spec(key).repeated()
+            }
             fn tiny_help_message(key: &str) -> String {
                 if key == "" {
                     format!("{}...", $tyname.to_string())
@@ -427,17 +2113,31 @@ impl_from!(isize, "isize");
 impl AutoArgs for f64 {
     const REQUIRES_INPUT: bool = true;
     fn parse_internal(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
-        let the_arg = String::parse_internal(key, args)?;
+        let value = Self::parse_once(key, args)?;
+        not_repeated(key, args)?;
+        Ok(value)
+    }
+    fn parse_once(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
+        let the_arg = String::parse_once(key, args).map_err(|e| e.expecting("FLOAT"))?;
+        let invalid = |source: Source| Error::OptionValueParsingFailed {
+            flag: key.to_string(),
+            value: the_arg.clone(),
+            expected: "FLOAT".to_string(),
+            index: None,
+            env: None,
+            source: Some(source),
+        };
         #[cfg(feature = "meval")]
-        let value = meval::eval_str(the_arg)
-            .map_err(|e| Error::OptionValueParsingFailed(key.to_string(), e.to_string()));
+        let value = meval::eval_str(&the_arg).map_err(|e| invalid(message_source(e.to_string())));
         #[cfg(not(feature = "meval"))]
-        let value = f64::from_str(&the_arg)
-            .map_err(|e| Error::OptionValueParsingFailed(key.to_string(), e.to_string()));
+        let value = f64::from_str(&the_arg).map_err(|e| invalid(std::sync::Arc::new(e)));
         value
     }
+    fn spec(key: &str) -> ArgSpec {
+        ArgSpec::value(key, "FLOAT")
+    }
     fn tiny_help_message(key: &str) -> String {
-        if key == "" {
+        if key.is_empty() {
             "FLOAT".to_string()
         } else {
             format!("{} FLOAT", key)
@@ -450,11 +2150,11 @@ impl AutoArgs for Vec<f64> {
     fn parse_internal(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
         let mut res: Self = Vec::new();
         loop {
-            match <f64>::parse_internal(key, args) {
+            match <f64>::parse_once(key, args) {
                 Ok(val) => {
                     res.push(val);
                 }
-                Err(Error::MissingOption(_)) => {
+                Err(Error::MissingOption { .. }) => {
                     return Ok(res);
                 }
                 Err(e) => {
@@ -463,6 +2163,9 @@ impl AutoArgs for Vec<f64> {
             }
         }
     }
+    fn spec(key: &str) -> ArgSpec {
+        f64::spec(key).repeated()
+    }
     fn tiny_help_message(key: &str) -> String {
         format!("{} ...", f64::tiny_help_message(key))
     }
@@ -473,6 +2176,12 @@ impl AutoArgs for f32 {
     fn parse_internal(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
         f64::parse_internal(key, args).map(|v| v as f32)
     }
+    fn parse_once(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
+        f64::parse_once(key, args).map(|v| v as f32)
+    }
+    fn spec(key: &str) -> ArgSpec {
+        f64::spec(key)
+    }
     fn tiny_help_message(key: &str) -> String {
         f64::tiny_help_message(key)
     }
@@ -483,11 +2192,11 @@ impl AutoArgs for Vec<f32> {
     fn parse_internal(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
         let mut res: Self = Vec::new();
         loop {
-            match <f32>::parse_internal(key, args) {
+            match <f32>::parse_once(key, args) {
                 Ok(val) => {
                     res.push(val);
                 }
-                Err(Error::MissingOption(_)) => {
+                Err(Error::MissingOption { .. }) => {
                     return Ok(res);
                 }
                 Err(e) => {
@@ -496,6 +2205,9 @@ impl AutoArgs for Vec<f32> {
             }
         }
     }
+    fn spec(key: &str) -> ArgSpec {
+        f32::spec(key).repeated()
+    }
     fn tiny_help_message(key: &str) -> String {
         Vec::<f64>::tiny_help_message(key)
     }
@@ -506,6 +2218,14 @@ impl<T> AutoArgs for std::marker::PhantomData<T> {
     fn parse_internal(_key: &str, _args: &mut Vec<OsString>) -> Result<Self, Error> {
         Ok(std::marker::PhantomData)
     }
+    fn flag_names(_key: &str) -> Vec<String> {
+        Vec::new()
+    }
+    fn spec(key: &str) -> ArgSpec {
+        let mut spec = ArgSpec::new(key, ArgKind::Group(Vec::new()));
+        spec.optional = true;
+        spec
+    }
     fn tiny_help_message(_key: &str) -> String {
         "".to_string()
     }
@@ -520,7 +2240,7 @@ mod tests {
         key: &'static str,
         result: T,
     ) {
-        let mut args: Vec<_> = args.iter().map(|s| OsString::from(s)).collect();
+        let mut args: Vec<_> = args.iter().map(OsString::from).collect();
         assert_eq!(T::parse_internal(key, &mut args).unwrap(), result);
     }
     fn should_parse_completely<T: PartialEq + AutoArgs + std::fmt::Debug>(
@@ -528,9 +2248,9 @@ mod tests {
         key: &'static str,
         result: T,
     ) {
-        let mut args: Vec<_> = args.iter().map(|s| OsString::from(s)).collect();
+        let mut args: Vec<_> = args.iter().map(OsString::from).collect();
         assert_eq!(T::parse_internal(key, &mut args).unwrap(), result);
-        if args.len() != 0 {
+        if !args.is_empty() {
             println!("args remaining: {:?}", args);
             assert_eq!(args.len(), 0);
         }
@@ -540,7 +2260,7 @@ mod tests {
         args: &'static [&'static str],
         key: &'static str,
     ) {
-        let mut args: Vec<_> = args.iter().map(|s| OsString::from(s)).collect();
+        let mut args: Vec<_> = args.iter().map(OsString::from).collect();
         assert!(T::parse_internal(key, &mut args).is_err());
     }
 
@@ -654,6 +2374,7 @@ mod tests {
         second: T,
     }
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn derive_test_pair() {
         println!(
             "help:\n{}",
@@ -727,11 +2448,7 @@ mod tests {
         );
 
         let flags = &["--right-right"];
-        should_parse_completely(
-            flags,
-            "",
-            Either::<u32, Either<u8, Option<u32>>>::Right(Either::Right(None)),
-        );
+        shouldnt_parse::<Either<u32, Either<u8, Option<u32>>>>(flags, "");
 
         let flags = &["--right-right", "5"];
         should_parse_completely(
@@ -758,6 +2475,7 @@ mod tests {
         );
     }
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn option() {
         let flags = &["--foo", "good"];
         should_parse(flags, "--foo", Some("good".to_string()));
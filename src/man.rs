@@ -0,0 +1,196 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Man pages, generated from [`AutoArgs::spec`].
+//!
+//! A page is created by [`render`], or by running a program with
+//! `--generate-man` if its type has `#[auto_args(man)]`.  For example
+//!
+//! ```bash
+//! myprog --generate-man > myprog.1
+//! ```
+
+use crate::{ArgKind, ArgSpec, AutoArgs};
+
+/// Create a man page for the command whose arguments are parsed as a
+/// `T`, in the `man(7)` format.
+///
+/// The description is the doc comment on `T`, and every flag is listed
+/// with the whole of its doc comment.  The variants of an enum are
+/// given a section of their own, as are subcommands.
+pub fn render<T: AutoArgs>() -> String {
+    let program = crate::program_name::<T>();
+    let spec = T::spec("");
+    let mut out = format!(".TH {} 1\n", escape(&program.to_uppercase()));
+
+    out.push_str(".SH NAME\n");
+    let summary = spec.doc.lines().next().unwrap_or("").trim();
+    if summary.is_empty() {
+        out.push_str(&format!("{}\n", escape(&program)));
+    } else {
+        out.push_str(&format!("{} \\- {}\n", escape(&program), escape(summary)));
+    }
+
+    out.push_str(".SH SYNOPSIS\n");
+    out.push_str(&format!(".B {}\n", escape(&program)));
+    let args = T::tiny_help_message("");
+    if !args.trim().is_empty() {
+        out.push_str(&format!("{}\n", escape(args.trim())));
+    }
+
+    if !spec.doc.is_empty() {
+        out.push_str(".SH DESCRIPTION\n");
+        out.push_str(&paragraphs(&spec.doc, ".PP"));
+    }
+
+    let mut page = Page::default();
+    page.collect(&spec);
+    if !page.options.is_empty() {
+        out.push_str(".SH OPTIONS\n");
+        out.push_str(&page.options);
+    }
+    if !page.arguments.is_empty() {
+        out.push_str(".SH ARGUMENTS\n");
+        out.push_str(&page.arguments);
+    }
+    // Sections may be found while rendering others.
+    let mut i = 0;
+    while i < page.sections.len() {
+        let (title, alternatives) = page.sections[i].clone();
+        let mut nested = Page::default();
+        out.push_str(&format!(".SH {}\n", escape(&title)));
+        out.push_str(&nested.variants(&alternatives));
+        page.sections.extend(nested.sections);
+        i += 1;
+    }
+    out
+}
+
+/// The parts of a man page, as found from an `ArgSpec`.
+#[derive(Default)]
+struct Page {
+    options: String,
+    arguments: String,
+    /// Alternatives needing a section of their own, along with its
+    /// title.
+    sections: Vec<(String, ArgSpec)>,
+}
+
+impl Page {
+    fn collect(&mut self, spec: &ArgSpec) {
+        match spec.kind {
+            ArgKind::Switch if !spec.positional => {
+                self.options.push_str(&entry(&flag(spec, None), &spec.doc))
+            }
+            ArgKind::Switch => (),
+            ArgKind::Value { ref metavar } => {
+                if spec.positional {
+                    let name = format!("\\fI{}\\fR", escape(&spec.name));
+                    self.arguments.push_str(&entry(&name, &spec.doc));
+                } else if !spec.name.is_empty() {
+                    self.options
+                        .push_str(&entry(&flag(spec, Some(metavar)), &spec.doc));
+                }
+            }
+            ArgKind::Group(ref specs) => {
+                for s in specs {
+                    self.collect(s);
+                }
+            }
+            ArgKind::Alternatives(_) => {
+                let title = if spec.positional {
+                    "COMMANDS".to_string()
+                } else {
+                    match spec.name.trim_start_matches('-') {
+                        "" => "ALTERNATIVES".to_string(),
+                        name => name.replace('-', " ").to_uppercase(),
+                    }
+                };
+                self.sections.push((title, spec.clone()));
+            }
+        }
+    }
+
+    /// The body of the section for the alternatives `spec`.
+    fn variants(&mut self, spec: &ArgSpec) -> String {
+        let mut out = String::new();
+        if !spec.doc.is_empty() {
+            out.push_str(&paragraphs(&spec.doc, ".PP"));
+        }
+        let specs = match spec.kind {
+            ArgKind::Alternatives(ref specs) => specs,
+            _ => return out,
+        };
+        if !spec.positional {
+            out.push_str(".PP\nOnly one of the following may be given.\n");
+        }
+        for s in specs {
+            out.push_str(&format!(".SS {}\n", escape(&s.name)));
+            if !s.doc.is_empty() {
+                out.push_str(&paragraphs(&s.doc, ".PP"));
+            }
+            if let ArgKind::Switch = s.kind {
+                continue;
+            }
+            let mut variant = Page::default();
+            variant.collect(s);
+            out.push_str(&variant.options);
+            out.push_str(&variant.arguments);
+            self.sections.extend(variant.sections);
+        }
+        out
+    }
+}
+
+/// The flag of `spec` in bold, with its short flag and any value.
+fn flag(spec: &ArgSpec, metavar: Option<&str>) -> String {
+    let mut out = format!("\\fB{}\\fR", escape(&spec.name));
+    if let Some(c) = spec.short {
+        out = format!("\\fB\\-{}\\fR, {}", escape(&c.to_string()), out);
+    }
+    if let Some(metavar) = metavar {
+        out.push_str(&format!(" \\fI{}\\fR", escape(metavar)));
+    }
+    out
+}
+
+/// A tagged paragraph describing an argument.
+fn entry(tag: &str, doc: &str) -> String {
+    let mut out = format!(".TP\n{}\n", tag);
+    if !doc.is_empty() {
+        out.push_str(&paragraphs(doc, ".IP"));
+    }
+    out
+}
+
+/// The paragraphs of `doc`, each started by the request `start`,
+/// except that the first is not started by `.IP` since it follows a
+/// tag.
+fn paragraphs(doc: &str, start: &str) -> String {
+    let mut out = String::new();
+    for (i, paragraph) in doc.split("\n\n").enumerate() {
+        if i > 0 || start != ".IP" {
+            out.push_str(start);
+            out.push('\n');
+        }
+        for line in paragraph.lines() {
+            let line = line.trim();
+            if line.starts_with('.') || line.starts_with('\'') {
+                out.push_str("\\&");
+            }
+            out.push_str(&line.replace('\\', "\\e"));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Escape `s` for roff, with its hyphens as in a command.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\e").replace('-', "\\-")
+}
@@ -0,0 +1,53 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+use auto_args::AutoArgs;
+
+/// Count the words in files.
+///
+/// Every file is read in turn.
+#[derive(AutoArgs, PartialEq, Debug)]
+#[auto_args(name = "wc", after_help = "Report bugs to the author.")]
+struct Opt {
+    /// Count lines instead.
+    lines: bool,
+}
+
+#[test]
+fn about() {
+    std::env::set_var("COLUMNS", "80");
+    assert_eq!(
+        Opt::help(),
+        "Count the words in files.
+
+Every file is read in turn.
+
+USAGE:
+  wc  [--lines]
+
+  [--lines] Count lines instead.
+
+
+Report bugs to the author.
+
+For more information try --help"
+    );
+    assert!(Opt::short_help().starts_with("Count the words in files.\n\nUSAGE:\n  wc "));
+    assert!(Opt::usage().starts_with("USAGE:\n  wc  [--lines]"));
+}
+
+#[test]
+fn no_about() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Bare {
+        lines: bool,
+    }
+    assert!(Bare::help().starts_with("USAGE:\n"));
+    assert!(!Bare::help().contains("Report bugs"));
+}
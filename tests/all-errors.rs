@@ -0,0 +1,100 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::{AutoArgs, Error};
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Size {
+    width: u32,
+    height: u32,
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Opt {
+    name: String,
+    count: u32,
+    size: Size,
+}
+
+fn parse(args: &[&str]) -> Result<Opt, Vec<Error>> {
+    Opt::parse_vec_all_errors(args.iter().map(|a| a.into()).collect())
+}
+
+#[test]
+fn all_errors() {
+    assert_eq!(
+        Ok(Opt {
+            name: "x".to_string(),
+            count: 3,
+            size: Size {
+                width: 1,
+                height: 2
+            },
+        }),
+        parse(&[
+            "--name",
+            "x",
+            "--count",
+            "3",
+            "--size-width",
+            "1",
+            "--size-height",
+            "2"
+        ])
+    );
+
+    let errors = parse(&["--count", "many", "--size-width", "1", "--bogus", "extra"]).unwrap_err();
+    for e in errors.iter() {
+        println!("error: {}\n", e);
+    }
+    assert_eq!(5, errors.len());
+    assert_eq!(
+        Error::MissingOption {
+            flag: "--name".to_string(),
+            expected: "STRING".to_string()
+        },
+        errors[0]
+    );
+    assert!(matches!(
+        errors[1],
+        Error::OptionValueParsingFailed {
+            ref flag,
+            index: Some(2),
+            ..
+        } if flag == "--count"
+    ));
+    assert_eq!(
+        vec![
+            Error::MissingOption {
+                flag: "--size-height".to_string(),
+                expected: "u32".to_string()
+            },
+            Error::UnexpectedOption {
+                option: "--bogus".to_string(),
+                index: Some(5),
+                suggestion: None
+            },
+            Error::UnexpectedArgument {
+                value: "extra".to_string(),
+                index: Some(6)
+            },
+        ],
+        errors[2..].to_vec()
+    );
+}
+
+#[test]
+fn first_error_from_parse_vec() {
+    assert_eq!(
+        Err(Error::MissingOption {
+            flag: "--name".to_string(),
+            expected: "STRING".to_string()
+        }),
+        Opt::from_iter(["", "--count", "many", "extra"])
+    );
+}
@@ -0,0 +1,233 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::completions::{candidates, dynamic, generate, respond, Shell};
+use auto_args::AutoArgs;
+use std::path::PathBuf;
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+enum Shape {
+    /// A circle
+    Circle { radius: f64 },
+    /// A square
+    Square,
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+#[auto_args(completions)]
+struct Opt {
+    /// Be verbose
+    #[auto_args(short = 'v')]
+    verbose: bool,
+    /// The output file
+    output: PathBuf,
+    /// Where to start
+    start: Point,
+    /// Directories to search
+    include: Vec<PathBuf>,
+    /// The shape
+    shape: Shape,
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+#[auto_args(subcommand)]
+enum Command {
+    /// Build the project
+    Build {
+        /// Build with optimizations
+        release: bool,
+    },
+    /// Run a file
+    Run {
+        #[auto_args(positional)]
+        file: PathBuf,
+    },
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Tool {
+    /// Print more information
+    quiet: bool,
+    command: Command,
+}
+
+#[test]
+fn completions_flag() {
+    const { assert!(Opt::COMPLETIONS) };
+    const { assert!(!Tool::COMPLETIONS) };
+}
+
+#[test]
+fn shell_from_str() {
+    assert_eq!("zsh".parse::<Shell>().ok(), Some(Shell::Zsh));
+    assert!(matches!(
+        "tcsh".parse::<Shell>(),
+        Err(auto_args::Error::OptionValueParsingFailed { .. })
+    ));
+}
+
+#[test]
+fn bash() {
+    let script = generate::<Opt>(Shell::Bash, "myprog");
+    println!("{}", script);
+    for word in [
+        "--verbose",
+        "-v",
+        "--output",
+        "--start-x",
+        "--start-y",
+        "--shape-circle-radius",
+        "--shape-square",
+    ] {
+        assert!(script.contains(word), "missing {}", word);
+    }
+    assert!(script.contains("--output) COMPREPLY=( $(compgen -f -- \"$cur\") )"));
+    assert!(script.ends_with("complete -F _myprog myprog\n"));
+}
+
+#[test]
+fn bash_subcommands() {
+    let script = generate::<Tool>(Shell::Bash, "tool");
+    println!("{}", script);
+    assert!(script.contains("build|run) command=\"$word\""));
+    assert!(script.contains("compgen -W \"--release\""));
+    assert!(script.contains("compgen -W \"--quiet build run\""));
+}
+
+#[test]
+fn zsh() {
+    let script = generate::<Opt>(Shell::Zsh, "myprog");
+    println!("{}", script);
+    assert!(script.starts_with("#compdef myprog\n"));
+    assert!(script.contains("'(-v --verbose)'{-v,--verbose}'[Be verbose]'"));
+    assert!(script.contains("'--output[The output file]: :_files'"));
+    assert!(script.contains("'--shape-square[A square]'"));
+    assert!(script.contains("'--start-x[]: : '"));
+}
+
+#[test]
+fn zsh_subcommands() {
+    let script = generate::<Tool>(Shell::Zsh, "tool");
+    println!("{}", script);
+    assert!(script.contains("'build[Build the project]'"));
+    assert!(script.contains("'--release[Build with optimizations]'"));
+    assert!(script.contains("'*: :_files'"));
+}
+
+#[test]
+fn fish() {
+    let script = generate::<Opt>(Shell::Fish, "myprog");
+    println!("{}", script);
+    assert!(script.starts_with("complete -c myprog -f\n"));
+    assert!(script.contains("complete -c myprog -l verbose -s v -d 'Be verbose'\n"));
+    assert!(script.contains("complete -c myprog -l output -r -F -d 'The output file'\n"));
+    assert!(script.contains("complete -c myprog -l shape-circle-radius -r\n"));
+}
+
+#[test]
+fn fish_subcommands() {
+    let script = generate::<Tool>(Shell::Fish, "tool");
+    println!("{}", script);
+    assert!(!script.contains("complete -c tool -f\n"));
+    assert!(script.contains(
+        "complete -c tool -n __fish_use_subcommand -f -a build -d 'Build the project'\n"
+    ));
+    assert!(script.contains(
+        "complete -c tool -n '__fish_seen_subcommand_from build' -l release -d 'Build with optimizations'\n"
+    ));
+}
+
+fn complete<T: AutoArgs>(words: &[&str]) -> Vec<String> {
+    let words: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+    candidates::<T>(&words).into_iter().map(|(c, _)| c).collect()
+}
+
+#[test]
+fn dynamic_flags() {
+    let all = complete::<Opt>(&[""]);
+    assert!(all.contains(&"--verbose".to_string()));
+    assert!(all.contains(&"--shape-circle-radius".to_string()));
+    assert!(all.contains(&"--shape-square".to_string()));
+    assert_eq!(complete::<Opt>(&["--st"]), vec!["--start-x", "--start-y"]);
+    assert_eq!(
+        candidates::<Opt>(&["--verb".to_string()]),
+        vec![("--verbose".to_string(), "Be verbose".to_string())]
+    );
+}
+
+#[test]
+fn dynamic_given_flags() {
+    let after = complete::<Opt>(&["-v", "--include", "src", "--shape-square", ""]);
+    assert!(!after.contains(&"--verbose".to_string()));
+    assert!(!after.contains(&"--shape-square".to_string()));
+    assert!(!after.contains(&"--shape-circle-radius".to_string()));
+    assert!(after.contains(&"--include".to_string()));
+    assert!(after.contains(&"--output".to_string()));
+
+    let after = complete::<Opt>(&["--shape-circle-radius", "1", "--shape"]);
+    assert_eq!(after, Vec::<String>::new());
+}
+
+#[test]
+fn dynamic_values() {
+    assert_eq!(complete::<Opt>(&["--output", "Cargo.t"]), vec!["Cargo.toml"]);
+    assert_eq!(complete::<Opt>(&["--include", "sr"]), vec!["src/"]);
+    assert_eq!(complete::<Opt>(&["--start-x", ""]), Vec::<String>::new());
+}
+
+#[test]
+fn dynamic_subcommands() {
+    assert_eq!(complete::<Tool>(&[""]), vec!["--quiet", "build", "run"]);
+    assert_eq!(complete::<Tool>(&["--quiet", "b"]), vec!["build"]);
+    assert_eq!(complete::<Tool>(&["build", ""]), vec!["--release"]);
+    assert_eq!(complete::<Tool>(&["run", "Cargo.t"]), vec!["Cargo.toml"]);
+    assert_eq!(complete::<Tool>(&["run", "--", "--"]), Vec::<String>::new());
+}
+
+#[test]
+fn dynamic_respond() {
+    let args = |words: &[&str]| -> Vec<std::ffi::OsString> {
+        words.iter().map(|w| w.into()).collect()
+    };
+    assert_eq!(
+        respond::<Tool>(Shell::Bash, "tool", &args(&["--", "--quiet", ""])),
+        "build\nrun\n"
+    );
+    assert_eq!(
+        respond::<Tool>(Shell::Zsh, "tool", &args(&["--", "--quiet", ""])),
+        "build:Build the project\nrun:Run a file\n"
+    );
+    assert_eq!(
+        respond::<Tool>(Shell::Fish, "tool", &args(&["--", ""])),
+        "--quiet\tPrint more information\nbuild\tBuild the project\nrun\tRun a file\n"
+    );
+    assert_eq!(
+        respond::<Tool>(Shell::Fish, "tool", &[]),
+        dynamic(Shell::Fish, "tool")
+    );
+}
+
+#[test]
+fn dynamic_scripts() {
+    let bash = dynamic(Shell::Bash, "my-prog");
+    println!("{}", bash);
+    assert!(bash.contains("COMPLETE=bash my-prog -- \"${COMP_WORDS[@]:1:COMP_CWORD}\""));
+    assert!(bash.ends_with("complete -F _my_prog my-prog\n"));
+    let zsh = dynamic(Shell::Zsh, "my-prog");
+    println!("{}", zsh);
+    assert!(zsh.starts_with("#compdef my-prog\n"));
+    assert!(zsh.contains("COMPLETE=zsh my-prog -- \"${(@)words[2,CURRENT]}\""));
+    let fish = dynamic(Shell::Fish, "my-prog");
+    assert!(fish.contains("env COMPLETE=fish my-prog -- (commandline -opc)[2..-1] (commandline -ct)"));
+}
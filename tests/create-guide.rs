@@ -1,4 +1,10 @@
-#[macro_use]
+// The doc comments in this file are prose for the guide rather than
+// documentation of the code around them.  Within the guide itself,
+// prose that comes right before an example is written as ordinary
+// comments, so that it does not become the doc comment of the type,
+// which is shown in its help.
+#![allow(unused_doc_comments, dead_code, clippy::empty_line_after_doc_comments)]
+
 extern crate auto_args;
 
 use auto_args::AutoArgs;
@@ -51,17 +57,19 @@ use std::io::{BufRead, Write};
 
 /// I want to note that `AutoArgs` *almost always* produces long flags.
 /// This is because I feel that long flags are generally the easiest
-/// to use.  If you want to fine-tune your command-line interface,
-/// `AutoArgs` may not be for you.
+/// to use.  Short flags are only given to single-letter fields and to
+/// fields that ask for one.  If you want to fine-tune your
+/// command-line interface, `AutoArgs` may not be for you.
 
 #[test]
 fn guide() {
+    std::env::set_var("COLUMNS", "80"); // so the help wraps the same everywhere
     let mut strings = Vec::new();
-    /// ## Just a flag
+    // ## Just a flag
 
-    /// Most often, you will define a struct as your type.  We'll
-    /// start out with the simplest case, which is a single boolean
-    /// field within that struct.
+    // Most often, you will define a struct as your type.  We'll
+    // start out with the simplest case, which is a single boolean
+    // field within that struct.
     #[derive(AutoArgs)]
     // START CODE
     struct Foo {
@@ -74,12 +82,12 @@ fn guide() {
     /// and the following help message.
     strings.push(Foo::help());
     // INSERT STRING
-    /// A single boolean flag is treated as an optional flag.
+    // A single boolean flag is treated as an optional flag.
 
-    /// ## Adding help information
+    // ## Adding help information
 
-    /// We add help information simply by adding ordinary doc comments
-    /// to our struct.
+    // We add help information simply by adding ordinary doc comments
+    // to our struct.
     #[derive(AutoArgs)]
     #[allow(non_snake_case)]
     // START CODE
@@ -96,15 +104,20 @@ fn guide() {
     /// and the following help message.
     strings.push(Help::help());
     // INSERT STRING
-    /// I would always documentation for actual projects, so I'll try
-    /// to model that here, even though these examples are all
-    /// fictitious.
+    // I would always documentation for actual projects, so I'll try
+    // to model that here, even though these examples are all
+    // fictitious.
+
+    // The whole of a doc comment is shown by `--help`, with its
+    // paragraphs and lists indented under the flag, while `-h` shows
+    // only the first line of each.  A doc comment may also be given
+    // as `#[doc = include_str!("...")]`.
 
-    /// ## How the flag is determined
+    // ## How the flag is determined
 
-    /// We saw above that the flag just had `--` prepended to the
-    /// field name.  The rule in general is only slightly more
-    /// complicated: every underscore is replaced with a `-`.
+    // We saw above that the flag just had `--` prepended to the
+    // field name.  The rule in general is only slightly more
+    // complicated: every underscore is replaced with a `-`.
     #[derive(AutoArgs)]
     #[allow(non_snake_case)]
     // START CODE
@@ -120,15 +133,62 @@ fn guide() {
     /// This gives the following usage.
     strings.push(Flags::help());
     // INSERT STRING
-    /// Thus you can create most any flag name you care for, and it is
-    /// easy to tell which flag corresponds to which field in your
-    /// struct.
+    // Thus you can create most any flag name you care for, and it is
+    // easy to tell which flag corresponds to which field in your
+    // struct.
+
+    // ## Renaming flags
+
+    // If you would rather not name your fields after your flags, you
+    // can give a flag name explicitly with `#[auto_args(rename =
+    // "...")]` on a field or variant.  The convention used for all
+    // the fields and variants of a type can be chosen with
+    // `#[auto_args(rename_all = "...")]` on the type itself, which
+    // accepts `"kebab-case"` (the default described above),
+    // `"snake_case"`, `"lowercase"` and `"verbatim"`.
+    #[allow(non_snake_case)]
+    // IGNORE CODE
+    #[derive(AutoArgs)]
+    #[auto_args(rename_all = "lowercase")]
+    struct Renamed {
+        /// The minimum temperature.
+        min_T: f64,
+        /// The number of atoms.
+        #[auto_args(rename = "atoms")]
+        N: u32,
+    }
+    // STOP CODE
+    /// This gives the following usage.
+    strings.push(Renamed::help());
+    // INSERT STRING
+
+    // ## Short flags
+
+    // A field whose flag is a single letter, like `N` below, may also
+    // be given with a single dash, as in `-N 100`.  Any other field
+    // can be given a short flag with `#[auto_args(short = 'x')]`.
+    // Boolean short flags may be combined as in `-vq`, and values may
+    // be attached as in `-N100`.
+    #[allow(non_snake_case)]
+    // IGNORE CODE
+    #[derive(AutoArgs)]
+    struct Short {
+        /// The number of atoms.
+        N: u32,
+        /// Print excess messages.
+        #[auto_args(short = 'v')]
+        verbose: bool,
+    }
+    // STOP CODE
+    /// This gives the following usage.
+    strings.push(Short::help());
+    // INSERT STRING
 
-    /// ## Other types
+    // ## Other types
 
-    /// You can add most standard library types to your struct,
-    /// basically anything that can be read or parsed from a `&str`.
-    /// I'd recommend sticking to owned types.
+    // You can add most standard library types to your struct,
+    // basically anything that can be read or parsed from a `&str`.
+    // I'd recommend sticking to owned types.
     #[derive(AutoArgs)]
     #[allow(non_snake_case)]
     // START CODE
@@ -145,24 +205,24 @@ fn guide() {
     strings.push(Types::help());
     // INSERT STRING
 
-    /// I should note that integer types do allow their value to be
-    /// specified using scientific notation, as in `1e6` rather than
-    /// `1000000`.  This is in different from rust's `FromStr`
-    /// implementation.  AutoArgs does reject floating point values that
-    /// cannot be reversibly converted to the integer type that is
-    /// requested.
-    ///
-    /// Furthermore, when providing numerical user input, users may
-    /// specify an expression such as `1/3` or `sqrt(2)`.  This is
-    /// most useful for floating point input where makes it easier to
-    /// give high-precision input when needed, but may also be helpful
-    /// for integers.
+    // I should note that integer types do allow their value to be
+    // specified using scientific notation, as in `1e6` rather than
+    // `1000000`.  This is in different from rust's `FromStr`
+    // implementation.  AutoArgs does reject floating point values that
+    // cannot be reversibly converted to the integer type that is
+    // requested.
+    //
+    // Furthermore, when providing numerical user input, users may
+    // specify an expression such as `1/3` or `sqrt(2)`.  This is
+    // most useful for floating point input where makes it easier to
+    // give high-precision input when needed, but may also be helpful
+    // for integers.
 
-    /// ## Optional flags
+    // ## Optional flags
 
-    /// In the previous examples, every flag (except a `bool` flag)
-    /// was required to be specified by the user.  If you want a flag
-    /// to be optional, you just use the standard `Option` type.
+    // In the previous examples, every flag (except a `bool` flag)
+    // was required to be specified by the user.  If you want a flag
+    // to be optional, you just use the standard `Option` type.
     #[derive(AutoArgs)]
     // START CODE
     struct Optional {
@@ -173,12 +233,132 @@ fn guide() {
     /// This gives the following usage.
     strings.push(Optional::help());
     // INSERT STRING
-    /// The value is then `None` if the user did not specify that flag.
+    // The value is then `None` if the user did not specify that flag.
+    // A flag that is given with a missing or invalid value is still
+    // an error.  An `Option` of a struct is all or nothing: giving
+    // some of its required flags without the others is an error that
+    // lists the ones that are missing.
+
+    // ## Default values
+
+    // If there is a sensible value to use when the user does not
+    // specify a flag, you can give it with `#[auto_args(default =
+    // ...)]`, or use `#[auto_args(default)]` to fall back on
+    // `Default::default()`.  The field then keeps its ordinary type.
+    // A `bool` flag is already false when it is not given, so it
+    // cannot have a default.
+    // IGNORE CODE
+    #[derive(AutoArgs)]
+    struct Defaults {
+        /// The filling fraction.
+        #[auto_args(default = 0.5)]
+        filling_fraction: f64,
+        /// The number of steps.
+        #[auto_args(default)]
+        steps: u64,
+    }
+    // STOP CODE
+    /// This gives the following usage.
+    strings.push(Defaults::help());
+    // INSERT STRING
+
+    // ## Repeated flags
+
+    // Giving a flag that takes a single value more than once, such as
+    // `--seed 1 --seed 2`, is an error.  If you would rather the last
+    // occurrence win, as many Unix tools do, you can say so with
+    // `#[auto_args(repeated = "last")]`, either on a field or on the
+    // type to apply to all of its fields.  A field can go back to the
+    // default with `#[auto_args(repeated = "error")]`.  A `Vec` field
+    // collects every occurrence regardless.
+    // IGNORE CODE
+    #[derive(AutoArgs)]
+    #[auto_args(repeated = "last")]
+    struct Repeated {
+        /// The random seed.
+        seed: u64,
+        /// The output file.
+        #[auto_args(repeated = "error")]
+        output: String,
+    }
+    // STOP CODE
+    /// This gives the following usage.
+    strings.push(Repeated::help());
+    // INSERT STRING
+
+    // ## Environment variables
+
+    // A field can fall back on an environment variable when its flag
+    // is not given, with `#[auto_args(env = "...")]`.  Alternatively,
+    // `#[auto_args(env_prefix = "...")]` on the type gives every
+    // field a variable named by the prefix followed by the field name
+    // in upper case.  A flag on the command line always wins over the
+    // environment.
+    // IGNORE CODE
+    #[derive(AutoArgs)]
+    #[auto_args(env_prefix = "APP_")]
+    struct Environment {
+        /// The number of threads.
+        #[auto_args(default = 1)]
+        threads: u32,
+        /// The output directory.
+        #[auto_args(env = "OUTPUT_DIR")]
+        output: Option<String>,
+    }
+    // STOP CODE
+    /// This gives the following usage.
+    strings.push(Environment::help());
+    // INSERT STRING
+
+    // ## Positional arguments
+
+    // A field marked `#[auto_args(positional)]` is given without a
+    // flag, and is shown in the usage by its name in upper case.
+    // Positional fields are filled in the order they are declared,
+    // from the arguments that are left once the flags have been
+    // parsed, so a `Vec` collects all that remain and belongs last.
+    // Anything after a `--` is positional, even if it looks like a
+    // flag.
+    // IGNORE CODE
+    #[derive(AutoArgs)]
+    struct Positional {
+        /// Print each file as it is copied.
+        verbose: bool,
+        /// The file to copy.
+        #[auto_args(positional)]
+        input: std::path::PathBuf,
+        /// Where to copy it.
+        #[auto_args(positional)]
+        output: Vec<std::path::PathBuf>,
+    }
+    // STOP CODE
+    /// This gives the following usage.
+    strings.push(Positional::help());
+    // INSERT STRING
 
-    /// ## Exclusive flags
+    // If you are wrapping another program, a `Vec<OsString>` field
+    // marked `#[auto_args(trailing)]` receives everything after the
+    // first `--` exactly as given, even arguments that look like
+    // flags.
+    // IGNORE CODE
+    #[derive(AutoArgs)]
+    struct Trailing {
+        /// The program to run.
+        #[auto_args(positional)]
+        program: String,
+        /// Arguments for the program.
+        #[auto_args(trailing)]
+        args: Vec<std::ffi::OsString>,
+    }
+    // STOP CODE
+    /// This gives the following usage.
+    strings.push(Trailing::help());
+    // INSERT STRING
 
-    /// If you want to make certain flags/values mutually exclusive,
-    /// you use an `enum` (just as always, in rust).
+    // ## Exclusive flags
+
+    // If you want to make certain flags/values mutually exclusive,
+    // you use an `enum` (just as always, in rust).
     #[derive(AutoArgs)]
     // START CODE
     enum Exclusive {
@@ -187,7 +367,7 @@ fn guide() {
         First {
             /// This is the "a" value
             a: String,
-            /// Only the first line of comment shows up in help.
+            /// Only the first line of comment shows up with `-h`.
             /// This is the "b" value, which you cannot specify unless
             /// you also specify the "a" value.
             b: String,
@@ -204,26 +384,59 @@ fn guide() {
     /// and the following help message.
     strings.push(Exclusive::help());
     // INSERT STRING
-    /// This example illustrates the three kinds of `enum` variants.
-    /// Sadly, the help message does not indicate that these flags are
-    /// exlusive.  However, if a user tries to specify both `--third`
-    /// and `--second FOO`, however, they will get a nice error
-    /// message.  Note that you cannot use a tuple variant with more
-    /// than one field.
-
-    /// Note that the rules for constructing flags from enum variants
-    /// are more complicated than for struct fields.  This is because
-    /// by convention variants are given `CamelCase` names, which
-    /// aren't suitable as flags.  If a variant name contains an
-    /// underscore, then it is treated like a field name (as described
-    /// above), with any trailing underscores removed.  Otherwise the
-    /// name is converted from `CamelCase` to `kebab-case`.
-
-    /// ## Nesting types
-
-    /// You can use any `AutoArgs` type as a field within a struct or
-    /// enum.  Doing so will give flag names that combine the nested
-    /// field names.
+    // This example illustrates the three kinds of `enum` variants.
+    // Sadly, the help message does not indicate that these flags are
+    // exlusive.  However, if a user tries to specify both `--third`
+    // and `--second-flag FOO`, they will get an error saying that the two
+    // cannot be used together.  The variant is chosen by which flags
+    // are given, so if only `--first-a` is given, the error will
+    // be that `--first-b` is missing.  Note that you cannot use
+    // a tuple variant with more than one field.
+
+    // Note that the rules for constructing flags from enum variants
+    // are more complicated than for struct fields.  This is because
+    // by convention variants are given `CamelCase` names, which
+    // aren't suitable as flags.  If a variant name contains an
+    // underscore, then it is treated like a field name (as described
+    // above), with any trailing underscores removed.  Otherwise the
+    // name is converted from `CamelCase` to `kebab-case`.
+
+    // ## Subcommands
+
+    // An `enum` marked `#[auto_args(subcommand)]` is instead
+    // selected by a bare word, as in `git commit`.  The fields of
+    // the chosen variant are given after that word, with no prefix
+    // on their flags, and the variants are listed in the help along
+    // with their doc comments.  A field holding the subcommand is
+    // best placed after any flags of its own struct.
+    // IGNORE CODE
+    #[derive(AutoArgs)]
+    #[auto_args(subcommand)]
+    enum Command {
+        /// Build the project.
+        Build {
+            /// Build with optimizations.
+            release: bool,
+        },
+        /// Remove build products.
+        Clean,
+    }
+    #[derive(AutoArgs)]
+    struct Tool {
+        /// Print more information.
+        verbose: bool,
+        command: Command,
+    }
+    // STOP CODE
+    /// This gives the following usage.
+    strings.push(Tool::help());
+    // INSERT STRING
+
+    // ## Nesting types
+
+    // You can use any `AutoArgs` type as a field within a struct or
+    // enum.  Doing so will give flag names that combine the nested
+    // field names.
     // IGNORE CODE
     #[derive(AutoArgs)]
     /// I'm not putting doc-comments on `x` and `y`, because auto_args
@@ -248,16 +461,22 @@ fn guide() {
     strings.push(Nested::help());
     // INSERT STRING
 
-    /// ## Flattened nesting types
+    // ## Flattened nesting types
 
-    /// As you say in the last example, nesting types allows you to
-    /// make your own complex types that can be reused.  Sometimes,
-    /// however, you would like to nest structs for a different
-    /// reason: to separate concerns in the code.  In this case, you
-    /// may not want the nesting to be visible in the user interface.
-    /// This can be acheived with a leading underscore on a field
-    /// name.  The catch is that when you do this, you could run into
-    /// a runtime error if you have duplicate field names.
+    // As you say in the last example, nesting types allows you to
+    // make your own complex types that can be reused.  Sometimes,
+    // however, you would like to nest structs for a different
+    // reason: to separate concerns in the code.  In this case, you
+    // may not want the nesting to be visible in the user interface.
+    // This can be acheived with `#[auto_args(flatten)]` on a field
+    // (or on an enum variant).  A leading underscore on a field name
+    // does the same thing.  The catch is that when you do this, you
+    // could end up with two fields given the same flag.  `AutoArgs`
+    // catches duplicates among the fields of one type, and flattening
+    // the same type twice, at compile time.  Those within different
+    // flattened types panic when your program first parses its
+    // arguments or shows its help.  The variants of an enum may share
+    // flags, since only one of them is given.
     // IGNORE CODE
     #[derive(AutoArgs)]
     struct MyConfig {
@@ -271,8 +490,10 @@ fn guide() {
     }
     #[derive(AutoArgs)]
     struct Flattened {
-        _mine: MyConfig,
-        _yours: YourConfig,
+        #[auto_args(flatten)]
+        mine: MyConfig,
+        #[auto_args(flatten)]
+        yours: YourConfig,
     }
     // STOP CODE
     /// This gives the following usage.
@@ -281,8 +502,94 @@ fn guide() {
     /// and the following help message.
     strings.push(Flattened::help());
     // INSERT STRING
-    /// This may be a good idea if `MyConfig` and `YourConfig` are
-    /// implementation details that your user need not be aware of.
+    // This may be a good idea if `MyConfig` and `YourConfig` are
+    // implementation details that your user need not be aware of.
+
+    // ## Describing the program
+
+    // The doc comment on the type you parse describes the whole
+    // program, and is shown at the top of the help (only its first
+    // line for `-h`).  The name of the program is normally the one it
+    // was run by, but may be given with `#[auto_args(name = "...")]`,
+    // or at run time with `auto_args::set_program_name`, so that the
+    // help does not change when the program is run through a symlink.
+    // Any text in `#[auto_args(after_help = "...")]` goes at the end
+    // of the help.
+    // IGNORE CODE
+    #[derive(AutoArgs)]
+    /// Count the words in files.
+    #[auto_args(name = "wc", after_help = "Report bugs to the author.")]
+    struct About {
+        /// Count lines instead.
+        lines: bool,
+    }
+    // STOP CODE
+    /// This gives the following help message.
+    strings.push(About::help());
+    // INSERT STRING
+
+    // ## Shell completions
+
+    // With `#[auto_args(completions)]` on the type you parse,
+    // `from_args` accepts a hidden `--generate-completions SHELL`
+    // flag, which prints a completion script for bash, zsh or fish
+    // and exits.  The script offers every flag along with the first
+    // line of its doc comment, the variants of subcommands, and file
+    // names for `PathBuf` values.  You can also create the script
+    // yourself with `auto_args::completions::generate`.
+    // IGNORE CODE
+    #[derive(AutoArgs)]
+    #[auto_args(completions)]
+    struct Completions {
+        /// Print more information.
+        verbose: bool,
+        /// The file to read.
+        input: std::path::PathBuf,
+    }
+    // STOP CODE
+    // A user of bash could then run
+    // `myprog --generate-completions bash > ~/.local/share/bash-completion/completions/myprog`.
+
+    // Such a script offers every flag, even those that can no longer
+    // be given.  The same attribute lets the shell instead ask your
+    // program for the completions each time, which takes into account
+    // what has already been typed, such as which variant of an enum
+    // was chosen.  The user of bash would then run
+    // `source <(COMPLETE=bash myprog)`, and likewise for zsh and fish.
+
+    // ## Man pages
+
+    // Similarly, `#[auto_args(man)]` gives `from_args` a hidden
+    // `--generate-man` flag, which prints a man page and exits, as
+    // does `auto_args::man::render`.  The description is taken from
+    // the doc comment on the type, and unlike in the help every line
+    // of the doc comment on each flag is included.  The variants of
+    // an enum, and subcommands, are listed in sections of their own.
+    // IGNORE CODE
+    #[derive(AutoArgs)]
+    #[auto_args(man)]
+    /// Copy a file.
+    ///
+    /// The copy is made atomically, by writing to a temporary file.
+    struct Manual {
+        /// Print more information.
+        ///
+        /// This reports the number of bytes copied.
+        verbose: bool,
+    }
+    // STOP CODE
+
+    /// ## Reference documentation
+
+    /// For a book or web site, `auto_args::docs::markdown` and
+    /// `auto_args::docs::html` create a reference page giving the
+    /// usage, every flag with the whole of its doc comment, its type
+    /// and whether it is required, and the alternatives of each enum
+    /// in a nested section.
+    /// A program that builds a user interface from your flags can
+    /// instead run yours with `--help-json`, which prints a versioned
+    /// JSON description of every argument, if you put
+    /// `#[auto_args(help_json)]` on the type you parse.
 
     /// ## Other possibilities
 
@@ -305,16 +612,24 @@ fn guide() {
 
     let src = std::path::Path::new("tests/create-guide.rs");
     let dest = std::path::Path::new("src/guide.rs");
-    let mut f = std::fs::File::create(&dest).unwrap();
-    let i = std::fs::File::open(&src).unwrap();
+    let mut f = std::fs::File::create(dest).unwrap();
+    let i = std::fs::File::open(src).unwrap();
     let lines = std::io::BufReader::new(&i);
     let mut am_writing = false;
+    let mut in_guide = false;
     let mut chars_to_trim = 0;
     for line in lines.lines() {
         let l: String = line.unwrap();
+        if l.starts_with(&format!("fn {}()", "guide")) {
+            in_guide = true;
+        } else if l.contains(&format!("{}.{}()", "strings", "reverse")) {
+            in_guide = false;
+        }
         if l.contains(&format!("{}{}", "//", "/")) && !am_writing {
-            let l = l.replacen(&format!("{}{}", "//", "/"), "", 1);
-            writeln!(f, "//! {}", &l.trim()).unwrap();
+            let l = l.trim_start().replacen(&format!("{}{}", "//", "/"), "", 1);
+            // Keep any indentation beyond the first space, so that
+            // continued list items stay indented.
+            writeln!(f, "//! {}", l.strip_prefix(' ').unwrap_or(&l).trim_end()).unwrap();
         } else if l.contains(&format!("{} {}", "START", "CODE")) {
             am_writing = true;
             chars_to_trim = l.find(|c: char| !c.is_whitespace()).unwrap();
@@ -333,6 +648,13 @@ fn guide() {
                 writeln!(f, "//! {}", &ll).unwrap();
             }
             writeln!(f, "//! ```").unwrap();
+        } else if in_guide
+            && !am_writing
+            && l.trim_start().starts_with("//")
+            && !l.contains(&format!("{} {}", "INSERT", "STRING"))
+        {
+            let l = l.trim_start().replacen("//", "", 1);
+            writeln!(f, "//! {}", l.strip_prefix(' ').unwrap_or(&l).trim_end()).unwrap();
         } else if am_writing {
             writeln!(f, "//! {}", &l.split_at(chars_to_trim).1).unwrap();
         }
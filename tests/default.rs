@@ -0,0 +1,130 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::AutoArgs;
+use std::path::PathBuf;
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Opt {
+    /// Filling fraction
+    #[auto_args(default = 0.5)]
+    filling_fraction: f64,
+    /// Number of steps
+    #[auto_args(default)]
+    steps: u64,
+    /// Output directory
+    #[auto_args(default = "out")]
+    dir: PathBuf,
+    /// Name of the run
+    name: String,
+}
+
+#[test]
+#[allow(clippy::assertions_on_constants)]
+fn default_values() {
+    println!("help: {}", Opt::help());
+    assert!(Opt::REQUIRES_INPUT);
+    assert!(Opt::help().contains("Filling fraction [default: 0.5]"));
+    assert!(Opt::help().contains("Output directory [default: out]"));
+    assert!(Opt::help().contains("Number of steps\n"));
+    assert!(Opt::usage().contains("[--filling-fraction FLOAT]"));
+    assert!(Opt::usage().contains(" --name STRING"));
+
+    assert_eq!(
+        Opt {
+            filling_fraction: 0.5,
+            steps: 0,
+            dir: PathBuf::from("out"),
+            name: "run".to_string(),
+        },
+        Opt::from_iter(["", "--name", "run"]).unwrap()
+    );
+    assert_eq!(
+        Opt {
+            filling_fraction: 0.25,
+            steps: 10,
+            dir: PathBuf::from("elsewhere"),
+            name: "run".to_string(),
+        },
+        Opt::from_iter([
+            "",
+            "--name",
+            "run",
+            "--filling-fraction=0.25",
+            "--steps",
+            "10",
+            "--dir",
+            "elsewhere"
+        ])
+        .unwrap()
+    );
+    assert!(Opt::from_iter(["", "--name", "run", "--steps", "many"]).is_err());
+    assert!(Opt::from_iter(["", "--name", "run", "--steps"]).is_err());
+}
+
+#[test]
+#[allow(clippy::assertions_on_constants)]
+fn defaults_do_not_require_input() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct AllDefault {
+        #[auto_args(default = 3)]
+        threads: u32,
+    }
+    assert!(!AllDefault::REQUIRES_INPUT);
+    assert_eq!(
+        AllDefault { threads: 3 },
+        AllDefault::from_iter([""]).unwrap()
+    );
+
+    #[derive(AutoArgs, PartialEq, Debug)]
+    enum Mode {
+        Run(String),
+        Serve {
+            #[auto_args(default = 8080)]
+            port: u16,
+        },
+    }
+    assert_eq!(
+        Mode::Serve { port: 8080 },
+        Mode::from_iter(["", "--serve"]).unwrap()
+    );
+    assert_eq!(
+        Mode::Serve { port: 80 },
+        Mode::from_iter(["", "--serve", "--serve-port", "80"]).unwrap()
+    );
+}
+
+#[test]
+fn defaults_for_types_that_need_no_input() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Lists {
+        /// The sizes
+        #[auto_args(default = vec![1, 2])]
+        xs: Vec<u32>,
+        /// The level
+        #[auto_args(default = Some(3))]
+        level: Option<u8>,
+    }
+    println!("help: {}", Lists::help());
+    assert!(Lists::help().contains("The sizes [default: vec![1, 2]]"));
+    assert!(Lists::help().contains("The level [default: Some(3)]"));
+    assert_eq!(
+        Lists {
+            xs: vec![1, 2],
+            level: Some(3),
+        },
+        Lists::from_iter([""]).unwrap()
+    );
+    assert_eq!(
+        Lists {
+            xs: vec![5],
+            level: Some(1),
+        },
+        Lists::from_iter(["", "--xs", "5", "--level", "1"]).unwrap()
+    );
+}
@@ -22,7 +22,73 @@ fn simple_doc_comment() {
         "Should use the first line from the doc comment"
     );
     assert!(
-        !Opt::help().contains("Second line"),
-        "Should not use the second line from the doc comment"
+        Opt::help().contains("Second line"),
+        "Should use the second line from the doc comment in the full help"
+    );
+    println!("short help: {}", Opt::short_help());
+    assert!(Opt::short_help().contains("First line"));
+    assert!(
+        !Opt::short_help().contains("Second line"),
+        "Should not use the second line from the doc comment in the short help"
+    );
+}
+
+#[test]
+fn paragraphs() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Opt {
+        /// The number of threads.
+        ///
+        /// These are used:
+        ///
+        /// - for `parsing`, and
+        ///   also
+        /// - for output.
+        #[auto_args(default = 4)]
+        threads: u64,
+        /// Be quiet.
+        quiet: bool,
+    }
+    std::env::set_var("COLUMNS", "80");
+    let help = Opt::help();
+    println!("help: {}", help);
+    assert!(help.contains(
+        "  --threads u64 The number of threads. [default: 4]\n\n                These are used:\n\n                - for `parsing`, and\n                  also\n                - for output.\n  [--quiet]     Be quiet.\n"
+    ));
+    let short = Opt::short_help();
+    println!("short help: {}", short);
+    assert!(short.contains(
+        "  --threads u64 The number of threads. [default: 4]\n  [--quiet]     Be quiet.\n"
+    ));
+    assert_eq!(
+        Opt::spec("").kind,
+        auto_args::ArgKind::Group(vec![
+            {
+                let mut spec = auto_args::ArgSpec::value("--threads", "u64");
+                spec.doc = "The number of threads.\n\nThese are used:\n\n- for `parsing`, and\n  also\n- for output.".to_string();
+                spec.optional = true;
+                spec
+            },
+            {
+                let mut spec = auto_args::ArgSpec::new("--quiet", auto_args::ArgKind::Switch);
+                spec.doc = "Be quiet.".to_string();
+                spec.optional = true;
+                spec
+            },
+        ])
+    );
+}
+
+#[test]
+fn doc_macros() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Opt {
+        #[doc = concat!("The ", "name.")]
+        #[doc = include_str!("doc-comment.txt")]
+        name: String,
+    }
+    assert_eq!(
+        Opt::help_message("", ""),
+        "\t--name STRING\tThe name.\n\t\tRead from a file.\n"
     );
 }
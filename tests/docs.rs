@@ -0,0 +1,76 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::docs::{html, markdown};
+use auto_args::AutoArgs;
+use std::path::PathBuf;
+
+#[derive(AutoArgs, PartialEq, Debug)]
+enum Shape {
+    /// A circle
+    Circle {
+        /// The radius of the circle
+        radius: f64,
+    },
+    /// A square
+    Square,
+}
+
+/// Draw some shapes.
+///
+/// The shapes are drawn one after another, in the order given.
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Opt {
+    /// Be verbose
+    ///
+    /// This prints every shape as it is drawn.
+    #[auto_args(short = 'v')]
+    verbose: bool,
+    /// The number of threads
+    threads: Option<u32>,
+    /// Colors to use
+    color: Vec<String>,
+    /// The shape
+    shape: Shape,
+    /// The file to draw in
+    #[auto_args(positional)]
+    output: PathBuf,
+}
+
+#[test]
+fn markdown_page() {
+    let page = markdown::<Opt>();
+    println!("{}", page);
+    assert!(page.starts_with("# "));
+    assert!(page.contains(
+        "\n\nDraw some shapes.\n\nThe shapes are drawn one after another, in the order given.\n\n## Usage\n\n```text\n"
+    ));
+    assert!(page.contains("## Arguments\n\n### `OUTPUT`\n\n*PATH, required*\n\nThe file to draw in\n\n"));
+    assert!(page.contains(
+        "## Options\n\n### `--verbose`, `-v`\n\n*switch*\n\nBe verbose\n\nThis prints every shape as it is drawn.\n\n"
+    ));
+    assert!(page.contains("### `--threads`\n\n*u32, optional*\n\nThe number of threads\n\n"));
+    assert!(page.contains("### `--color`\n\n*STRING, optional, repeatable*\n\n"));
+    assert!(page.contains(
+        "## `--shape`\n\nThe shape\n\nOnly one of the following may be given.\n\n### `--shape-circle`\n\nA circle\n\n#### `--shape-circle-radius`\n\n*FLOAT, required*\n\nThe radius of the circle\n\n### `--shape-square`\n\nA square\n\n"
+    ));
+}
+
+#[test]
+fn html_page() {
+    let page = html::<Opt>();
+    println!("{}", page);
+    assert!(page.starts_with("<!DOCTYPE html>\n"));
+    assert!(page.ends_with("</body>\n</html>\n"));
+    assert!(page.contains("<h2 id=\"usage\">Usage</h2>\n<pre><code>"));
+    assert!(page.contains(
+        "<h3 id=\"verbose\"><code>--verbose</code>, <code>-v</code></h3>\n<p><em>switch</em></p>\n<p>Be verbose</p>\n<p>This prints every shape as it is drawn.</p>\n"
+    ));
+    assert!(page.contains("<h2 id=\"shape\"><code>--shape</code></h2>\n<p>The shape</p>\n"));
+    assert!(page.contains("<h4 id=\"shape-circle-radius\"><code>--shape-circle-radius</code></h4>\n"));
+}
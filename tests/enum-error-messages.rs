@@ -6,6 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![allow(unused_imports, clippy::needless_borrows_for_generic_args)]
+
 #[macro_use]
 extern crate auto_args;
 
@@ -43,14 +45,85 @@ fn enum_error_message() {
     assert!(EnumOpt::from_iter(&["", "--first-first", "hello", "--second-second", "5"]).is_err());
     assert_eq!(
         EnumOpt::from_iter(&["", "--first-first", "hello"]),
-        Err(auto_args::Error::MissingOption(
-            "--first-second".to_string()
-        ))
+        Err(auto_args::Error::MissingOption {
+            flag: "--first-second".to_string(),
+            expected: "STRING".to_string(),
+        })
+    );
+    assert_eq!(
+        EnumOpt::from_iter(&["", "--first-second", "hello"]),
+        Err(auto_args::Error::MissingOption {
+            flag: "--first-first".to_string(),
+            expected: "STRING".to_string(),
+        })
     );
-    // FIXME the error message depends on the order of arguments in the types in an annoying way.
-    // It would be better to check how many flags *could* be found in each variant, rather than
-    // quitting after the first failure.
 
-    // assert_eq!(EnumOpt::from_iter(&["", "--first-second", "hello"]),
-    //      Err(auto_args::Error::MissingOption("--first-first".to_string())));
+    let err = EnumOpt::from_iter(&["", "--first-first", "hello", "--second-second", "5"]).unwrap_err();
+    println!("error: {}", err);
+    assert_eq!(
+        err,
+        auto_args::Error::ConflictingOptions {
+            option: "--second-second".to_string(),
+            conflicts_with: "--first-first".to_string(),
+            index: Some(3),
+        }
+    );
+    assert_eq!(
+        EnumOpt::from_iter(&["", "--third-third", "1", "--second-second", "5"]),
+        Err(auto_args::Error::ConflictingOptions {
+            option: "--second-second".to_string(),
+            conflicts_with: "--third-third".to_string(),
+            index: Some(3),
+        })
+    );
+}
+
+#[test]
+fn enum_all_missing_options() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    enum EnumOpt {
+        First { first: String, second: String, third: String },
+        Second { second: i32 },
+    }
+    let errors = EnumOpt::parse_vec_all_errors(vec!["--first-second=x".into()]);
+    assert_eq!(
+        Err(vec![
+            auto_args::Error::MissingOption {
+                flag: "--first-first".to_string(),
+                expected: "STRING".to_string(),
+            },
+            auto_args::Error::MissingOption {
+                flag: "--first-third".to_string(),
+                expected: "STRING".to_string(),
+            },
+        ]),
+        errors
+    );
+    let errors = EnumOpt::parse_vec_all_errors(vec![
+        "--first-first=x".into(),
+        "--first-third=z".into(),
+        "--bogus".into(),
+    ]);
+    assert_eq!(
+        Err(vec![
+            auto_args::Error::MissingOption {
+                flag: "--first-second".to_string(),
+                expected: "STRING".to_string(),
+            },
+            auto_args::Error::UnexpectedOption {
+                option: "--bogus".to_string(),
+                index: Some(3),
+                suggestion: None,
+            },
+        ]),
+        errors
+    );
+
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Opt {
+        mode: EnumOpt,
+    }
+    let errors = Opt::parse_vec_all_errors(vec![]).unwrap_err();
+    assert_eq!(1, errors.len());
+    println!("error: {}", errors[0]);
 }
@@ -6,6 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![allow(dead_code)]
+
 use auto_args::AutoArgs;
 
 #[derive(Debug, AutoArgs)]
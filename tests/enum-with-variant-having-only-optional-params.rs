@@ -6,6 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![allow(clippy::assertions_on_constants, clippy::needless_borrows_for_generic_args)]
+
 use auto_args::AutoArgs;
 
 #[derive(Debug, AutoArgs, PartialEq, Eq)]
@@ -6,6 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![allow(non_camel_case_types, unused_imports, clippy::needless_borrows_for_generic_args)]
+
 #[macro_use]
 extern crate auto_args;
 
@@ -238,22 +240,70 @@ fn enum_missing_flag() {
     );
 
     assert_eq!(
-        Err(auto_args::Error::MissingOption(
-            "--other-hello-greeting".to_string()
-        )),
+        Err(auto_args::Error::MissingOption {
+            flag: "--other-hello-greeting".to_string(),
+            expected: "STRING".to_string(),
+        }),
         Opt::from_iter(&["", "--other-hello-hello", "World"])
     );
     assert_eq!(
-        Err(auto_args::Error::MissingOption(
-            "--nested-goodbye".to_string()
-        )),
+        Err(auto_args::Error::MissingOption {
+            flag: String::new(),
+            expected: Opt::tiny_help_message(""),
+        }),
         Opt::from_iter(&["--typo"])
     );
 
     assert_eq!(
-        Err(auto_args::Error::MissingOption(
-            "--nested-goodbye".to_string()
-        )),
+        Err(auto_args::Error::MissingOption {
+            flag: String::new(),
+            expected: Opt::tiny_help_message(""),
+        }),
         Opt::from_iter(&["--nested-hello-hello", "World"])
     );
 }
+
+#[test]
+fn enum_variant_chosen_by_flags() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    enum Nested {
+        Hello { hello: String, greeting: String },
+        Goodbye(String),
+    }
+    #[derive(AutoArgs, PartialEq, Debug)]
+    enum Opt {
+        Other(Nested),
+        Nested(Nested),
+    }
+    assert_eq!(
+        Err(auto_args::Error::MissingOption {
+            flag: "--nested-hello-greeting".to_string(),
+            expected: "STRING".to_string(),
+        }),
+        Opt::from_iter(&["", "--nested-hello-hello", "World"])
+    );
+    assert_eq!(
+        Err(auto_args::Error::ConflictingOptions {
+            option: "--nested-goodbye".to_string(),
+            conflicts_with: "--other-goodbye".to_string(),
+            index: Some(3),
+        }),
+        Opt::from_iter(&["", "--other-goodbye", "a", "--nested-goodbye", "b"])
+    );
+    let args = ["--other-hello-hello", "a", "--nested-goodbye", "b", "--extra"];
+    assert_eq!(
+        Err(vec![
+            auto_args::Error::ConflictingOptions {
+                option: "--nested-goodbye".to_string(),
+                conflicts_with: "--other-hello-hello".to_string(),
+                index: Some(3),
+            },
+            auto_args::Error::UnexpectedOption {
+                option: "--extra".to_string(),
+                index: Some(5),
+                suggestion: None,
+            },
+        ]),
+        Opt::parse_vec_all_errors(args.iter().map(|a| a.into()).collect())
+    );
+}
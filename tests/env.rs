@@ -0,0 +1,155 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::AutoArgs;
+
+// Each test uses its own environment variables, since tests run in
+// parallel within a single process.
+
+#[test]
+fn env_fallback() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Opt {
+        /// Number of threads
+        #[auto_args(env = "ENV_TEST_THREADS")]
+        threads: u32,
+        /// Be verbose
+        #[auto_args(env = "ENV_TEST_VERBOSE")]
+        verbose: bool,
+    }
+    println!("help: {}", Opt::help());
+    assert!(Opt::help().contains("Number of threads [env: ENV_TEST_THREADS]"));
+
+    std::env::remove_var("ENV_TEST_THREADS");
+    std::env::remove_var("ENV_TEST_VERBOSE");
+    assert!(Opt::from_iter([""]).is_err());
+
+    std::env::set_var("ENV_TEST_THREADS", "8");
+    std::env::set_var("ENV_TEST_VERBOSE", "yes");
+    assert_eq!(
+        Opt {
+            threads: 8,
+            verbose: true
+        },
+        Opt::from_iter([""]).unwrap()
+    );
+    assert_eq!(
+        Opt {
+            threads: 2,
+            verbose: true
+        },
+        Opt::from_iter(["", "--threads", "2"]).unwrap()
+    );
+    assert_eq!(
+        Opt {
+            threads: 3,
+            verbose: true
+        },
+        Opt::from_iter(["", "--threads=3"]).unwrap()
+    );
+
+    std::env::set_var("ENV_TEST_VERBOSE", "0");
+    assert_eq!(
+        Opt {
+            threads: 8,
+            verbose: false
+        },
+        Opt::from_iter([""]).unwrap()
+    );
+    assert_eq!(
+        Opt {
+            threads: 8,
+            verbose: true
+        },
+        Opt::from_iter(["", "--verbose"]).unwrap()
+    );
+
+    std::env::set_var("ENV_TEST_THREADS", "many");
+    let err = Opt::from_iter([""]).unwrap_err();
+    println!("error: {}", err);
+    assert!(err.to_string().contains("ENV_TEST_THREADS"));
+    std::env::remove_var("ENV_TEST_THREADS");
+    std::env::remove_var("ENV_TEST_VERBOSE");
+}
+
+#[test]
+fn env_prefix() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    #[auto_args(env_prefix = "ENV_PREFIX_TEST_")]
+    struct Opt {
+        /// The name
+        name: Option<String>,
+        /// The size
+        #[auto_args(default = 1)]
+        size: u8,
+        /// The color
+        #[auto_args(env = "ENV_PREFIX_TEST_COLOUR")]
+        color: Option<String>,
+    }
+    println!("help: {}", Opt::help());
+    assert!(Opt::help().contains("The name [env: ENV_PREFIX_TEST_NAME]"));
+    assert!(Opt::help().contains("The size [default: 1] [env: ENV_PREFIX_TEST_SIZE]"));
+    assert!(Opt::help().contains("The color [env: ENV_PREFIX_TEST_COLOUR]"));
+
+    assert_eq!(
+        Opt {
+            name: None,
+            size: 1,
+            color: None
+        },
+        Opt::from_iter([""]).unwrap()
+    );
+    std::env::set_var("ENV_PREFIX_TEST_NAME", "David");
+    std::env::set_var("ENV_PREFIX_TEST_SIZE", "5");
+    std::env::set_var("ENV_PREFIX_TEST_COLOUR", "blue");
+    assert_eq!(
+        Opt {
+            name: Some("David".to_string()),
+            size: 5,
+            color: Some("blue".to_string())
+        },
+        Opt::from_iter([""]).unwrap()
+    );
+    assert_eq!(
+        Opt {
+            name: Some("Goliath".to_string()),
+            size: 5,
+            color: Some("blue".to_string())
+        },
+        Opt::from_iter(["", "--name", "Goliath"]).unwrap()
+    );
+    std::env::remove_var("ENV_PREFIX_TEST_NAME");
+    std::env::remove_var("ENV_PREFIX_TEST_SIZE");
+    std::env::remove_var("ENV_PREFIX_TEST_COLOUR");
+}
+
+#[test]
+fn env_errors() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Opt {
+        /// Number of threads
+        #[auto_args(env = "ENV_ERROR_THREADS")]
+        threads: u32,
+    }
+    std::env::set_var("ENV_ERROR_THREADS", "many");
+    let err = Opt::from_iter([""]).unwrap_err();
+    match err {
+        auto_args::Error::OptionValueParsingFailed {
+            ref flag, ref env, ..
+        } => {
+            assert_eq!("--threads", flag);
+            assert_eq!(&Some("ENV_ERROR_THREADS"), env);
+        }
+        ref e => panic!("unexpected error {:?}", e),
+    }
+    assert!(err
+        .to_string()
+        .starts_with("invalid value `many` for `--threads` (from environment variable ENV_ERROR_THREADS)\n"));
+    assert!(err.to_json().contains(r#""flag":"--threads","#));
+    assert!(err.to_json().ends_with(r#","env":"ENV_ERROR_THREADS"}"#));
+}
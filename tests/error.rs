@@ -0,0 +1,177 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::{AutoArgs, Error};
+use std::ffi::OsString;
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Opt {
+    /// Number of threads
+    threads: u32,
+    /// The name
+    name: Option<String>,
+}
+
+#[test]
+fn invalid_value() {
+    let err = Opt::from_iter(["", "--name", "x", "--threads", "many"]).unwrap_err();
+    println!("error: {}", err);
+    match err {
+        Error::OptionValueParsingFailed {
+            ref flag,
+            ref value,
+            ref expected,
+            index,
+            ..
+        } => {
+            assert_eq!(flag, "--threads");
+            assert_eq!(value, "many");
+            assert_eq!(expected, "u32");
+            assert_eq!(index, Some(4));
+        }
+        ref e => panic!("unexpected error {:?}", e),
+    }
+    assert_eq!(
+        "invalid value `many` for `--threads`
+  --> argument 4
+   = expected: u32
+   = note: invalid digit found in string",
+        err.to_string()
+    );
+    let source = std::error::Error::source(&err).expect("there should be a source");
+    assert_eq!("invalid digit found in string", source.to_string());
+
+    let err = Opt::from_iter(["", "--threads=-3"]).unwrap_err();
+    println!("error: {}", err);
+    assert!(err.to_string().contains("\n  --> argument 1\n"));
+}
+
+#[test]
+fn missing_and_unexpected() {
+    let err = Opt::from_iter([""]).unwrap_err();
+    println!("error: {}", err);
+    assert_eq!(
+        "the required option `--threads` is missing
+   = help: try adding `--threads u32`",
+        err.to_string()
+    );
+
+    let err = Opt::from_iter(["", "--threads", "1", "--bogus", "--other"]).unwrap_err();
+    println!("error: {}", err);
+    assert_eq!(
+        Error::UnexpectedOption {
+            option: "--bogus".to_string(),
+            index: Some(3),
+            suggestion: None
+        },
+        err
+    );
+    assert_eq!("unexpected option `--bogus`\n  --> argument 3", err.to_string());
+}
+
+/// A type with a manual implementation of `AutoArgs`.
+#[derive(PartialEq, Debug)]
+struct Even(u32);
+
+impl AutoArgs for Even {
+    const REQUIRES_INPUT: bool = true;
+    fn parse_internal(key: &str, args: &mut Vec<OsString>) -> Result<Self, Error> {
+        let n = u32::parse_internal(key, args)?;
+        if n % 2 == 0 {
+            Ok(Even(n))
+        } else {
+            Err(Error::custom(format!("{} must be even, not {}", key, n)))
+        }
+    }
+    fn tiny_help_message(key: &str) -> String {
+        u32::tiny_help_message(key)
+    }
+}
+
+#[test]
+fn custom_error() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Pairs {
+        count: Even,
+    }
+    assert_eq!(
+        Pairs { count: Even(4) },
+        Pairs::from_iter(["", "--count", "4"]).unwrap()
+    );
+    let err = Pairs::from_iter(["", "--count", "5"]).unwrap_err();
+    assert_eq!(Error::custom("--count must be even, not 5"), err);
+    assert_eq!("--count must be even, not 5", err.to_string());
+    assert!(std::error::Error::source(&err).is_none());
+}
+
+#[test]
+fn help_requested() {
+    let err = Opt::from_iter(["", "--help"]).unwrap_err();
+    assert_eq!(Error::HelpRequested { help: Opt::help() }, err);
+    assert_eq!(Opt::help(), err.to_string());
+    assert!(!matches!(
+        Opt::from_iter(["", "--", "--help"]),
+        Err(Error::HelpRequested { .. })
+    ));
+}
+
+#[test]
+fn index_in_argv() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Three {
+        alpha: u32,
+        beta: u32,
+        gamma: u32,
+    }
+    assert_eq!(
+        Error::UnexpectedArgument {
+            value: "1".to_string(),
+            index: Some(7)
+        },
+        Three::from_iter(["", "--alpha", "1", "--beta", "1", "--gamma", "1", "1"]).unwrap_err()
+    );
+
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Clustered {
+        #[auto_args(short = 'v')]
+        verbose: bool,
+        #[auto_args(short = 'q')]
+        quiet: bool,
+        #[auto_args(short = 'n')]
+        number: u32,
+    }
+    assert_eq!(
+        Some(2),
+        match Clustered::from_iter(["", "-vqn", "many"]).unwrap_err() {
+            Error::OptionValueParsingFailed { index, .. } => index,
+            e => panic!("unexpected error {:?}", e),
+        }
+    );
+    assert_eq!(
+        Some(1),
+        match Clustered::from_iter(["", "-qnmany"]).unwrap_err() {
+            Error::OptionValueParsingFailed { index, .. } => index,
+            e => panic!("unexpected error {:?}", e),
+        }
+    );
+    assert_eq!(
+        Error::UnexpectedOption {
+            option: "-x".to_string(),
+            index: Some(3),
+            suggestion: None
+        },
+        Clustered::from_iter(["", "-n", "1", "-vxq"]).unwrap_err()
+    );
+    assert_eq!(
+        Error::UnexpectedArgument {
+            value: "extra".to_string(),
+            index: Some(4)
+        },
+        Clustered::from_iter(["", "-q", "-vn", "4", "extra"]).unwrap_err()
+    );
+}
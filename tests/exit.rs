@@ -0,0 +1,82 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::process::{Command, Output};
+
+/// Run the `errors` example, which cargo builds along with the tests,
+/// or which we build if only these tests were built.
+fn errors_example(args: &[&str], hook: bool) -> Output {
+    let mut path = std::env::current_exe().unwrap();
+    path.pop();
+    if path.ends_with("deps") {
+        path.pop();
+    }
+    path.push("examples");
+    path.push(format!("errors{}", std::env::consts::EXE_SUFFIX));
+    if !path.exists() {
+        let mut cargo = Command::new(env!("CARGO"));
+        cargo.args(["build", "--quiet", "--example", "errors"]);
+        if !cfg!(debug_assertions) {
+            cargo.arg("--release");
+        }
+        let built = cargo
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .status()
+            .expect("cargo should run");
+        assert!(built.success());
+    }
+    let mut command = Command::new(path);
+    command.args(args).env_remove("AUTO_ARGS_ERROR_FORMAT");
+    if hook {
+        command.env("ERRORS_HOOK", "1");
+    } else {
+        command.env_remove("ERRORS_HOOK");
+    }
+    command.output().expect("the errors example should run")
+}
+
+#[test]
+fn success() {
+    let output = errors_example(&["--threads", "4"], false);
+    assert!(output.status.success());
+    assert_eq!(
+        "Opt { threads: 4, name: None }\n",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+fn errors_on_stderr() {
+    let output = errors_example(&["--threads", "many", "--bogus"], false);
+    assert_eq!(Some(2), output.status.code());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    println!("stderr:\n{}", stderr);
+    assert!(stderr.starts_with("error: invalid value `many` for `--threads`\n  --> argument 2\n"));
+    assert!(stderr.contains("error: unexpected option `--bogus`\n  --> argument 3\n"));
+    assert!(stderr.contains("USAGE:"));
+}
+
+#[test]
+fn error_hook() {
+    let output = errors_example(&["--threads", "many", "--bogus"], true);
+    assert_eq!(Some(2), output.status.code());
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        "2 errors:\n  option_value_parsing_failed\n  unexpected_option\n",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn no_help_json_unless_asked_for() {
+    let output = errors_example(&["--threads", "4", "--help-json"], false);
+    assert_eq!(Some(2), output.status.code());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .starts_with("error: unexpected option `--help-json`\n"));
+}
@@ -0,0 +1,145 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::AutoArgs;
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Network {
+    /// The host to connect to
+    host: String,
+    /// The port to connect to
+    port: Option<u16>,
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Logging {
+    /// Be verbose
+    verbose: bool,
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Opt {
+    #[auto_args(flatten)]
+    network: Network,
+    #[auto_args(flatten)]
+    logging: Logging,
+    /// The number of retries
+    retries: u8,
+}
+
+#[test]
+fn flatten_fields() {
+    println!("help: {}", Opt::help());
+    assert!(Opt::help().contains("--host STRING "));
+    assert!(Opt::help().contains("[--port u16] "));
+    assert!(Opt::help().contains("[--verbose] "));
+    assert!(!Opt::help().contains("--network"));
+
+    assert_eq!(
+        Opt {
+            network: Network {
+                host: "example.com".to_string(),
+                port: None,
+            },
+            logging: Logging { verbose: true },
+            retries: 3,
+        },
+        Opt::from_iter(["", "--host", "example.com", "--verbose", "--retries", "3"]).unwrap()
+    );
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+enum Source {
+    /// Read from a file
+    File(String),
+    #[auto_args(flatten)]
+    Remote(Network),
+}
+
+#[test]
+fn flatten_variant() {
+    println!("help: {}", Source::help());
+    assert!(Source::help().contains("--file STRING "));
+    assert!(Source::help().contains("--host STRING "));
+    assert!(!Source::help().contains("--remote"));
+
+    assert_eq!(
+        Source::File("data".to_string()),
+        Source::from_iter(["", "--file", "data"]).unwrap()
+    );
+    assert_eq!(
+        Source::Remote(Network {
+            host: "example.com".to_string(),
+            port: Some(80),
+        }),
+        Source::from_iter(["", "--host", "example.com", "--port", "80"]).unwrap()
+    );
+}
+
+#[test]
+#[should_panic(expected = "the flag `--verbose` is given by more than one field")]
+fn flattened_flag_collision() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Inner {
+        verbose: bool,
+        level: Option<u8>,
+    }
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Outer {
+        #[auto_args(flatten)]
+        inner: Inner,
+        verbose: bool,
+    }
+    let _ = Outer::from_iter(["", "--verbose"]);
+}
+
+#[test]
+#[should_panic(expected = "the flag `--verbose` is given by more than one field")]
+fn flattened_flag_collision_in_help() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Inner {
+        verbose: bool,
+    }
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Outer {
+        #[auto_args(flatten)]
+        inner: Inner,
+        verbose: bool,
+    }
+    let _ = Outer::help();
+}
+
+#[test]
+fn flattened_variants_share_flags() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    enum Mode {
+        _A { verbose: bool, a: String },
+        _B { verbose: bool, b: String },
+    }
+    assert_eq!(
+        Mode::_A {
+            verbose: true,
+            a: "x".to_string()
+        },
+        Mode::from_iter(["", "--verbose", "--a", "x"]).unwrap()
+    );
+    assert_eq!(
+        Mode::_B {
+            verbose: false,
+            b: "y".to_string()
+        },
+        Mode::from_iter(["", "--b", "y"]).unwrap()
+    );
+    assert_eq!(
+        Mode::_B {
+            verbose: true,
+            b: "y".to_string()
+        },
+        Mode::from_iter(["", "--b", "y", "--verbose"]).unwrap()
+    );
+}
@@ -0,0 +1,77 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::{ArgSpec, AutoArgs, HELP_JSON_VERSION};
+
+#[derive(AutoArgs, PartialEq, Debug)]
+enum Shape {
+    /// A circle
+    Circle { radius: f64 },
+    /// A square
+    Square,
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+#[auto_args(help_json)]
+struct Opt {
+    /// Be "verbose"
+    #[auto_args(short = 'v')]
+    verbose: bool,
+    shape: Shape,
+}
+
+#[test]
+fn spec_json() {
+    let mut spec = ArgSpec::value("--n", "u32");
+    spec.short = Some('n');
+    spec.doc = "How many".to_string();
+    assert_eq!(
+        spec.to_json(),
+        r#"{"name":"--n","short":"n","doc":"How many","optional":false,"repeatable":false,"positional":false,"kind":"value","metavar":"u32","children":[]}"#
+    );
+}
+
+#[test]
+fn nested_json() {
+    assert_eq!(
+        Opt::spec("").to_json(),
+        concat!(
+            r#"{"name":"","short":null,"doc":"","optional":false,"repeatable":false,"positional":false,"kind":"group","metavar":null,"children":["#,
+            r#"{"name":"--verbose","short":"v","doc":"Be \"verbose\"","optional":true,"repeatable":false,"positional":false,"kind":"switch","metavar":null,"children":[]},"#,
+            r#"{"name":"--shape","short":null,"doc":"","optional":false,"repeatable":false,"positional":false,"kind":"alternatives","metavar":null,"children":["#,
+            r#"{"name":"--shape-circle","short":null,"doc":"A circle","optional":false,"repeatable":false,"positional":false,"kind":"group","metavar":null,"children":["#,
+            r#"{"name":"--shape-circle-radius","short":null,"doc":"","optional":false,"repeatable":false,"positional":false,"kind":"value","metavar":"FLOAT","children":[]}]},"#,
+            r#"{"name":"--shape-square","short":null,"doc":"A square","optional":false,"repeatable":false,"positional":false,"kind":"switch","metavar":null,"children":[]}]}]}"#,
+        )
+    );
+}
+
+#[test]
+fn help_json() {
+    let json = Opt::help_json();
+    assert!(json.starts_with(&format!("{{\"version\":{},\"program\":", HELP_JSON_VERSION)));
+    assert!(json.contains(",\"usage\":\"[--verbose] ("));
+    assert!(json.ends_with(&format!(",\"spec\":{}}}", Opt::spec("").to_json())));
+}
+
+#[test]
+fn opt_in() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Tool {
+        /// The schema to print
+        help_json: Option<String>,
+    }
+    const { assert!(Opt::HELP_JSON) };
+    const { assert!(!Tool::HELP_JSON) };
+    assert_eq!(
+        Tool {
+            help_json: Some("v2".to_string())
+        },
+        Tool::from_iter(["", "--help-json", "v2"]).unwrap()
+    );
+}
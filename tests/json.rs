@@ -0,0 +1,53 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::{AutoArgs, Error};
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Opt {
+    threads: u32,
+    verbose: bool,
+}
+
+#[test]
+fn json_errors() {
+    let err = Opt::from_iter(["", "--threads", "many"]).unwrap_err();
+    assert_eq!("option_value_parsing_failed", err.kind());
+    println!("{}", err.to_json());
+    assert_eq!(
+        format!(
+            "{{\"kind\":\"option_value_parsing_failed\",\"message\":{:?},\
+             \"flag\":\"--threads\",\"value\":\"many\",\"expected\":\"u32\",\
+             \"index\":2,\"suggestions\":[]}}",
+            err.to_string()
+        ),
+        err.to_json()
+    );
+
+    let err = Opt::from_iter(["", "--threads", "1", "--verbos"]).unwrap_err();
+    println!("{}", err.to_json());
+    assert!(err.to_json().starts_with("{\"kind\":\"unexpected_option\","));
+    assert!(err
+        .to_json()
+        .ends_with("\"flag\":\"--verbos\",\"value\":null,\"expected\":null,\"index\":3,\"suggestions\":[\"--verbose\"]}"));
+
+    let err = Error::IncompleteOptions {
+        given: vec!["--a".to_string()],
+        missing: vec!["--b".to_string(), "--c".to_string()],
+    };
+    assert!(err
+        .to_json()
+        .ends_with("\"suggestions\":[],\"given\":[\"--a\"],\"missing\":[\"--b\",\"--c\"]}"));
+
+    let err = Error::custom("a \"quoted\"\ttab\\");
+    assert_eq!(
+        "{\"kind\":\"custom\",\"message\":\"a \\\"quoted\\\"\\ttab\\\\\",\"flag\":null,\
+         \"value\":null,\"expected\":null,\"index\":null,\"suggestions\":[]}",
+        err.to_json()
+    );
+}
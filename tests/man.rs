@@ -0,0 +1,109 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::AutoArgs;
+use std::path::PathBuf;
+
+#[derive(AutoArgs, PartialEq, Debug)]
+enum Shape {
+    /// A circle
+    Circle {
+        /// The radius of the circle
+        radius: f64,
+    },
+    /// A square
+    Square,
+}
+
+/// Draw some shapes.
+///
+/// The shapes are drawn one after another, in the order given.
+#[derive(AutoArgs, PartialEq, Debug)]
+#[auto_args(man)]
+struct Opt {
+    /// Be verbose
+    ///
+    /// This prints every shape as it is drawn.
+    #[auto_args(short = 'v')]
+    verbose: bool,
+    /// The number of threads
+    threads: Option<u32>,
+    /// The shape
+    shape: Shape,
+    /// The file to draw in
+    #[auto_args(positional)]
+    output: PathBuf,
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+#[auto_args(subcommand)]
+enum Command {
+    /// Build the project
+    Build {
+        /// Build with optimizations
+        release: bool,
+    },
+    /// Remove build products
+    Clean,
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Tool {
+    /// Print more information
+    quiet: bool,
+    command: Command,
+}
+
+#[test]
+fn man_flag() {
+    const { assert!(Opt::MAN) };
+    const { assert!(!Tool::MAN) };
+}
+
+#[test]
+fn full_doc_in_spec() {
+    assert_eq!(
+        Opt::spec("").doc,
+        "Draw some shapes.\n\nThe shapes are drawn one after another, in the order given."
+    );
+}
+
+#[test]
+fn man_page() {
+    let page = auto_args::man::render::<Opt>();
+    println!("{}", page);
+    assert!(page.starts_with(".TH "));
+    assert!(page.contains(".SH NAME\n"));
+    assert!(page.contains(" \\- Draw some shapes.\n"));
+    assert!(page.contains(".SH SYNOPSIS\n.B "));
+    assert!(page.contains(
+        ".SH DESCRIPTION\n.PP\nDraw some shapes.\n.PP\nThe shapes are drawn one after another, in the order given.\n"
+    ));
+    assert!(page.contains(
+        ".SH OPTIONS\n.TP\n\\fB\\-v\\fR, \\fB\\-\\-verbose\\fR\nBe verbose\n.IP\nThis prints every shape as it is drawn.\n"
+    ));
+    assert!(page.contains(".TP\n\\fB\\-\\-threads\\fR \\fIu32\\fR\nThe number of threads\n"));
+    assert!(page.contains(".SH ARGUMENTS\n.TP\n\\fIOUTPUT\\fR\nThe file to draw in\n"));
+    assert!(page.contains(".SH SHAPE\n.PP\nThe shape\n.PP\nOnly one of the following may be given.\n"));
+    assert!(page.contains(
+        ".SS \\-\\-shape\\-circle\n.PP\nA circle\n.TP\n\\fB\\-\\-shape\\-circle\\-radius\\fR \\fIFLOAT\\fR\nThe radius of the circle\n"
+    ));
+    assert!(page.contains(".SS \\-\\-shape\\-square\n.PP\nA square\n"));
+}
+
+#[test]
+fn man_page_subcommands() {
+    let page = auto_args::man::render::<Tool>();
+    println!("{}", page);
+    assert!(!page.contains(".SH DESCRIPTION"));
+    assert!(page.contains(".SH OPTIONS\n.TP\n\\fB\\-\\-quiet\\fR\nPrint more information\n"));
+    assert!(page.contains(
+        ".SH COMMANDS\n.SS build\n.PP\nBuild the project\n.TP\n\\fB\\-\\-release\\fR\nBuild with optimizations\n"
+    ));
+    assert!(page.contains(".SS clean\n.PP\nRemove build products\n"));
+}
@@ -1,3 +1,5 @@
+
+#![allow(clippy::needless_borrows_for_generic_args)]
 use auto_args::AutoArgs;
 
 #[derive(AutoArgs, PartialEq, Debug)]
@@ -6,6 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![allow(unused_imports, clippy::needless_borrows_for_generic_args)]
+
 #[macro_use]
 extern crate auto_args;
 
@@ -23,3 +25,88 @@ fn required_option() {
 
     assert!(Opt::from_iter(&["", "--arg"]).is_err());
 }
+
+#[test]
+fn optional_value() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Opt {
+        threads: Option<u32>,
+        name: String,
+    }
+    assert_eq!(
+        Opt {
+            threads: None,
+            name: "x".to_string()
+        },
+        Opt::from_iter(&["", "--name", "x"]).unwrap()
+    );
+    assert_eq!(
+        Opt {
+            threads: Some(4),
+            name: "x".to_string()
+        },
+        Opt::from_iter(&["", "--threads", "4", "--name", "x"]).unwrap()
+    );
+
+    let err = Opt::from_iter(&["", "--threads", "abc", "--name", "x"]).unwrap_err();
+    println!("error: {}", err);
+    assert!(matches!(
+        err,
+        auto_args::Error::OptionValueParsingFailed {
+            ref flag,
+            index: Some(2),
+            ..
+        } if flag == "--threads"
+    ));
+    assert_eq!(
+        Err(auto_args::Error::OptionWithoutAValue {
+            flag: "--threads".to_string(),
+            expected: "u32".to_string(),
+            index: Some(3)
+        }),
+        Opt::from_iter(&["", "--name", "x", "--threads"])
+    );
+}
+
+#[test]
+fn optional_struct() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Size {
+        width: u32,
+        height: u32,
+        depth: u32,
+        units: Option<String>,
+    }
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Opt {
+        size: Option<Size>,
+    }
+    assert_eq!(Opt { size: None }, Opt::from_iter(&[""]).unwrap());
+    assert_eq!(
+        Opt {
+            size: Some(Size {
+                width: 1,
+                height: 2,
+                depth: 3,
+                units: None,
+            })
+        },
+        Opt::from_iter(&["", "--size-width", "1", "--size-height", "2", "--size-depth", "3"])
+            .unwrap()
+    );
+
+    let err = Opt::from_iter(&["", "--size-height", "2", "--size-units", "cm"]).unwrap_err();
+    println!("error: {}", err);
+    assert_eq!(
+        auto_args::Error::IncompleteOptions {
+            given: vec!["--size-height".to_string(), "--size-units".to_string()],
+            missing: vec!["--size-width".to_string(), "--size-depth".to_string()],
+        },
+        err
+    );
+    assert_eq!(
+        "`--size-height`, `--size-units` cannot be given without `--size-width`, `--size-depth`\n   \
+         = help: give all of these options, or none of them",
+        err.to_string()
+    );
+}
@@ -0,0 +1,178 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::{AutoArgs, Error};
+use std::path::PathBuf;
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Copy {
+    /// Be verbose
+    #[auto_args(short = 'v')]
+    verbose: bool,
+    /// The file to read
+    #[auto_args(positional)]
+    input: PathBuf,
+    /// The files to write
+    #[auto_args(positional)]
+    output: Vec<PathBuf>,
+}
+
+#[test]
+fn positional_fields() {
+    println!("help: {}", Copy::help());
+    assert!(Copy::usage().contains(" [--verbose] INPUT OUTPUT...\n"));
+    assert!(Copy::help().contains("  INPUT           The file to read\n"));
+    assert!(Copy::help().contains("  OUTPUT          The files to write\n"));
+
+    assert_eq!(
+        Copy {
+            verbose: false,
+            input: PathBuf::from("a"),
+            output: vec![],
+        },
+        Copy::from_iter(["", "a"]).unwrap()
+    );
+    assert_eq!(
+        Copy {
+            verbose: true,
+            input: PathBuf::from("a"),
+            output: vec![PathBuf::from("b"), PathBuf::from("c")],
+        },
+        Copy::from_iter(["", "a", "--verbose", "b", "c"]).unwrap()
+    );
+    assert_eq!(
+        Copy {
+            verbose: true,
+            input: PathBuf::from("a"),
+            output: vec![PathBuf::from("--verbose")],
+        },
+        Copy::from_iter(["", "-v", "a", "--", "--verbose"]).unwrap()
+    );
+    assert_eq!(
+        Copy {
+            verbose: false,
+            input: PathBuf::from("-"),
+            output: vec![PathBuf::from("-1.5")],
+        },
+        Copy::from_iter(["", "-", "-1.5"]).unwrap()
+    );
+    assert_eq!(
+        Err(Error::UnexpectedOption {
+            option: "-z".to_string(),
+            index: Some(1),
+            suggestion: None
+        }),
+        Copy::from_iter(["", "-z", "x"])
+    );
+
+    assert_eq!(
+        Err(Error::MissingArgument {
+            name: "INPUT".to_string()
+        }),
+        Copy::from_iter(["", "--verbose"])
+    );
+    let err = Copy::from_iter([""]).unwrap_err();
+    println!("error: {}", err);
+    assert_eq!("the required argument `INPUT` is missing", err.to_string());
+    assert_eq!(
+        Err(Error::UnexpectedOption {
+            option: "--bogus".to_string(),
+            index: Some(2),
+            suggestion: None
+        }),
+        Copy::from_iter(["", "a", "--bogus"])
+    );
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Scale {
+    /// The factor to scale by
+    #[auto_args(default = 2)]
+    factor: u32,
+    /// The number to scale
+    #[auto_args(positional)]
+    value: u32,
+    /// The units of the number
+    #[auto_args(positional, default = "m")]
+    units: String,
+}
+
+#[test]
+fn positional_with_flags() {
+    println!("help: {}", Scale::help());
+    assert!(Scale::usage().contains(" [--factor u32] VALUE [UNITS]\n"));
+    assert!(Scale::help().contains("The units of the number [default: m]"));
+
+    assert_eq!(
+        Scale {
+            factor: 3,
+            value: 5,
+            units: "m".to_string(),
+        },
+        Scale::from_iter(["", "--factor", "3", "5"]).unwrap()
+    );
+    assert_eq!(
+        Scale {
+            factor: 2,
+            value: 5,
+            units: "km".to_string(),
+        },
+        Scale::from_iter(["", "5", "km"]).unwrap()
+    );
+    assert_eq!(
+        Err(Error::UnexpectedArgument {
+            value: "extra".to_string(),
+            index: Some(3)
+        }),
+        Scale::from_iter(["", "5", "km", "extra"])
+    );
+    assert_eq!(
+        Err(Error::UnexpectedOption {
+            option: "-z".to_string(),
+            index: Some(1),
+            suggestion: None
+        }),
+        Scale::from_iter(["", "-z", "5"])
+    );
+    let err = Scale::from_iter(["", "five"]).unwrap_err();
+    println!("error: {}", err);
+    assert!(err.to_string().contains("VALUE"));
+}
+
+#[test]
+fn positional_in_flattened_struct() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Inner {
+        #[auto_args(positional)]
+        input: String,
+    }
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Outer {
+        #[auto_args(flatten)]
+        inner: Inner,
+        name: String,
+    }
+    assert_eq!(
+        Outer {
+            inner: Inner {
+                input: "file".to_string()
+            },
+            name: "x".to_string(),
+        },
+        Outer::from_iter(["", "--name", "x", "file"]).unwrap()
+    );
+    assert_eq!(
+        Outer {
+            inner: Inner {
+                input: "file".to_string()
+            },
+            name: "x".to_string(),
+        },
+        Outer::from_iter(["", "file", "--name", "x"]).unwrap()
+    );
+}
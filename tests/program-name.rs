@@ -0,0 +1,23 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+use auto_args::AutoArgs;
+
+#[derive(AutoArgs, PartialEq, Debug)]
+#[auto_args(name = "wc")]
+struct Opt {
+    lines: bool,
+}
+
+#[test]
+fn set_program_name() {
+    auto_args::set_program_name("mytool");
+    assert!(Opt::usage().starts_with("USAGE:\n  mytool  [--lines]"));
+    assert!(Opt::help_json().contains(r#""program":"mytool""#));
+}
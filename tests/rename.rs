@@ -0,0 +1,113 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::AutoArgs;
+
+#[test]
+fn rename_field() {
+    #[allow(non_snake_case)]
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Opt {
+        /// The minimum temperature
+        #[auto_args(rename = "min-temperature")]
+        min_T: f64,
+        /// Be loud
+        #[auto_args(rename = "loud")]
+        _verbose: bool,
+    }
+    println!("help: {}", Opt::help());
+    assert!(Opt::help().contains("--min-temperature FLOAT"));
+    assert!(Opt::help().contains("[--loud]"));
+    assert!(!Opt::help().contains("--min-T"));
+    assert!(Opt::usage().contains("--min-temperature FLOAT"));
+
+    assert_eq!(
+        Opt {
+            min_T: 0.5,
+            _verbose: true
+        },
+        Opt::from_iter(["", "--min-temperature", "0.5", "--loud"]).unwrap()
+    );
+    assert!(Opt::from_iter(["", "--min-T", "0.5"]).is_err());
+}
+
+#[test]
+fn rename_nested() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Inner {
+        #[auto_args(rename = "size")]
+        inner_size: u32,
+    }
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Opt {
+        #[auto_args(rename = "box")]
+        container: Inner,
+    }
+    println!("help: {}", Opt::help());
+    assert!(Opt::help().contains("--box-size u32"));
+
+    assert_eq!(
+        Opt {
+            container: Inner { inner_size: 3 }
+        },
+        Opt::from_iter(["", "--box-size", "3"]).unwrap()
+    );
+}
+
+#[test]
+fn rename_all() {
+    #[allow(non_snake_case)]
+    #[derive(AutoArgs, PartialEq, Debug)]
+    #[auto_args(rename_all = "lowercase")]
+    struct Lower {
+        min_T: f64,
+    }
+    assert!(Lower::help().contains("--min-t FLOAT"));
+    assert_eq!(
+        Lower { min_T: 1.0 },
+        Lower::from_iter(["", "--min-t", "1"]).unwrap()
+    );
+
+    #[derive(AutoArgs, PartialEq, Debug)]
+    #[auto_args(rename_all = "snake_case")]
+    struct Snake {
+        well_width: f64,
+    }
+    assert!(Snake::help().contains("--well_width FLOAT"));
+
+    #[allow(non_snake_case)]
+    #[derive(AutoArgs, PartialEq, Debug)]
+    #[auto_args(rename_all = "verbatim")]
+    struct Verbatim {
+        min_T_: f64,
+    }
+    assert!(Verbatim::help().contains("--min_T_ FLOAT"));
+}
+
+#[test]
+fn rename_variants() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    #[auto_args(rename_all = "snake_case")]
+    enum Shape {
+        CellWidth(f64),
+        #[auto_args(rename = "vol")]
+        CellVolume { cell_volume: f64 },
+    }
+    println!("help: {}", Shape::help());
+    assert!(Shape::help().contains("--cell_width FLOAT"));
+    assert!(Shape::help().contains("--vol-cell_volume FLOAT"));
+
+    assert_eq!(
+        Shape::CellWidth(2.0),
+        Shape::from_iter(["", "--cell_width", "2"]).unwrap()
+    );
+    assert_eq!(
+        Shape::CellVolume { cell_volume: 8.0 },
+        Shape::from_iter(["", "--vol-cell_volume", "8"]).unwrap()
+    );
+}
@@ -0,0 +1,119 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::{AutoArgs, Error};
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Opt {
+    seed: u64,
+    name: Option<String>,
+    /// The last one wins
+    #[auto_args(repeated = "last")]
+    scale: f64,
+    values: Vec<u32>,
+}
+
+#[test]
+fn repeated_is_an_error() {
+    assert_eq!(
+        Opt {
+            seed: 1,
+            name: None,
+            scale: 2.0,
+            values: vec![3, 4],
+        },
+        Opt::from_iter(["", "--seed", "1", "--scale=2", "--values", "3", "--values", "4"]).unwrap()
+    );
+
+    let err = Opt::from_iter(["", "--seed", "1", "--scale=2", "--seed", "2"]).unwrap_err();
+    println!("error: {}", err);
+    assert_eq!(
+        Error::RepeatedOption {
+            flag: "--seed".to_string(),
+            index: Some(4),
+        },
+        err
+    );
+    assert_eq!(
+        "the option `--seed` was given more than once\n  --> argument 4\n   = help: give it only once",
+        err.to_string()
+    );
+    assert_eq!(
+        Err(Error::RepeatedOption {
+            flag: "--name".to_string(),
+            index: Some(5),
+        }),
+        Opt::from_iter(["", "--seed", "1", "--name=a", "--scale=2", "--name", "b"])
+    );
+}
+
+#[test]
+fn repeated_is_one_error() {
+    assert_eq!(
+        Err(vec![Error::RepeatedOption {
+            flag: "--seed".to_string(),
+            index: Some(3),
+        }]),
+        Opt::parse_vec_all_errors(
+            ["--seed", "1", "--seed", "2", "--scale", "1"]
+                .iter()
+                .map(|a| a.into())
+                .collect()
+        )
+    );
+    assert_eq!(
+        Err(vec![Error::RepeatedOption {
+            flag: "--seed".to_string(),
+            index: Some(4),
+        }]),
+        Opt::parse_vec_all_errors(
+            ["--seed=1", "--scale", "1", "--seed=2"]
+                .iter()
+                .map(|a| a.into())
+                .collect()
+        )
+    );
+}
+
+#[test]
+fn repeated_last_wins() {
+    assert_eq!(
+        Opt {
+            seed: 1,
+            name: None,
+            scale: 3.0,
+            values: Vec::new(),
+        },
+        Opt::from_iter(["", "--scale", "2", "--seed", "1", "--scale=5", "--scale", "3"]).unwrap()
+    );
+
+    #[derive(AutoArgs, PartialEq, Debug)]
+    #[auto_args(repeated = "last")]
+    struct Last {
+        #[auto_args(short = 'n')]
+        name: String,
+        verbose: bool,
+        #[auto_args(repeated = "error")]
+        seed: Option<u64>,
+    }
+    assert_eq!(
+        Last {
+            name: "b".to_string(),
+            verbose: true,
+            seed: None,
+        },
+        Last::from_iter(["", "-n", "a", "--verbose", "--name", "b"]).unwrap()
+    );
+    assert_eq!(
+        Err(Error::RepeatedOption {
+            flag: "--seed".to_string(),
+            index: Some(4),
+        }),
+        Last::from_iter(["", "-n", "a", "--seed=1", "--seed=2"])
+    );
+}
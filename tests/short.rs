@@ -0,0 +1,188 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::AutoArgs;
+
+#[allow(non_snake_case)]
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Opt {
+    /// Number of atoms
+    N: u64,
+    /// Temperature
+    T: Option<f64>,
+    /// Be verbose
+    #[auto_args(short = 'v')]
+    verbose: bool,
+    /// Be quiet
+    #[auto_args(short = 'q')]
+    quiet: bool,
+    /// Where to write
+    #[auto_args(short = 'o')]
+    output: Option<String>,
+}
+
+#[test]
+fn short_flags() {
+    println!("help: {}", Opt::help());
+    assert!(Opt::help().contains("-N, --N u64"));
+    assert!(Opt::help().contains("-v, [--verbose]"));
+    assert!(Opt::help().contains("-o, [--output STRING]"));
+
+    assert_eq!(
+        Opt {
+            N: 100,
+            T: Some(1.5),
+            verbose: false,
+            quiet: false,
+            output: None,
+        },
+        Opt::from_iter(["", "-N", "100", "-T", "1.5"]).unwrap()
+    );
+    assert_eq!(
+        Opt {
+            N: 100,
+            T: None,
+            verbose: true,
+            quiet: false,
+            output: Some("out".to_string()),
+        },
+        Opt::from_iter(["", "--N", "100", "-v", "-o", "out"]).unwrap()
+    );
+}
+
+#[test]
+fn clustered_short_flags() {
+    assert_eq!(
+        Opt {
+            N: 100,
+            T: None,
+            verbose: true,
+            quiet: true,
+            output: None,
+        },
+        Opt::from_iter(["", "-vq", "-N100"]).unwrap()
+    );
+    assert_eq!(
+        Opt {
+            N: 7,
+            T: None,
+            verbose: true,
+            quiet: false,
+            output: Some("file".to_string()),
+        },
+        Opt::from_iter(["", "-N=7", "-vofile"]).unwrap()
+    );
+    assert_eq!(
+        Opt {
+            N: 7,
+            T: None,
+            verbose: false,
+            quiet: true,
+            output: Some("file".to_string()),
+        },
+        Opt::from_iter(["", "-qo", "file", "-N", "7"]).unwrap()
+    );
+    assert!(Opt::from_iter(["", "-N", "7", "-vx"]).is_err());
+}
+
+#[test]
+fn no_automatic_short_when_nested() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Vec2d {
+        x: f64,
+        y: f64,
+    }
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Nested {
+        position: Vec2d,
+        /// Flattened fields keep their short flags
+        _velocity: Vec2d,
+    }
+    println!("help: {}", Nested::help());
+    assert!(Nested::help().contains("\n  --position-x FLOAT"));
+    assert!(Nested::help().contains("-x, --x FLOAT"));
+    assert_eq!(
+        Nested {
+            position: Vec2d { x: 1.0, y: 2.0 },
+            _velocity: Vec2d { x: 3.0, y: -4.0 },
+        },
+        Nested::from_iter([
+            "",
+            "--position-x",
+            "1",
+            "--position-y=2",
+            "-x",
+            "3",
+            "-y-4"
+        ])
+        .unwrap()
+    );
+}
+
+#[test]
+fn negative_numbers_are_not_short_flags() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Offset {
+        #[auto_args(short = 'd')]
+        delta: i32,
+    }
+    assert_eq!(
+        Offset { delta: -5 },
+        Offset::from_iter(["", "-d", "-5"]).unwrap()
+    );
+    assert_eq!(
+        Offset { delta: -5 },
+        Offset::from_iter(["", "--delta", "-5"]).unwrap()
+    );
+}
+
+#[test]
+fn values_are_not_short_flags() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Named {
+        name: String,
+        #[auto_args(short = 'v')]
+        verbose: bool,
+        #[auto_args(short = 'o')]
+        output: Option<String>,
+    }
+    assert_eq!(
+        Named {
+            name: "-v".to_string(),
+            verbose: false,
+            output: None,
+        },
+        Named::from_iter(["", "--name", "-v"]).unwrap()
+    );
+    assert_eq!(
+        Named {
+            name: "x".to_string(),
+            verbose: true,
+            output: Some("-v".to_string()),
+        },
+        Named::from_iter(["", "-o", "-v", "--name=x", "-v"]).unwrap()
+    );
+}
+
+#[test]
+#[should_panic(expected = "the flag `-v` is given by more than one field")]
+fn flattened_short_collision() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Inner {
+        #[auto_args(short = 'v')]
+        verbose: bool,
+    }
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Outer {
+        #[auto_args(flatten)]
+        inner: Inner,
+        #[auto_args(short = 'v')]
+        version: bool,
+    }
+    let _ = Outer::from_iter(["", "-v"]);
+}
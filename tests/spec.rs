@@ -0,0 +1,107 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::{ArgKind, ArgSpec, AutoArgs};
+use std::path::PathBuf;
+
+#[derive(AutoArgs, PartialEq, Debug)]
+enum Shape {
+    /// A circle
+    Circle { radius: f64 },
+    /// A square
+    Square,
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Opt {
+    /// Be verbose
+    #[auto_args(short = 'v')]
+    verbose: bool,
+    /// The number of threads
+    threads: Option<u32>,
+    /// The shape
+    shape: Shape,
+    /// The files to read
+    #[auto_args(positional)]
+    files: Vec<PathBuf>,
+}
+
+fn value(name: &str, metavar: &str, doc: &str) -> ArgSpec {
+    let mut spec = ArgSpec::value(name, metavar);
+    spec.doc = doc.to_string();
+    spec
+}
+
+#[test]
+fn spec() {
+    let mut verbose = ArgSpec::new("--verbose", ArgKind::Switch);
+    verbose.short = Some('v');
+    verbose.doc = "Be verbose".to_string();
+    verbose.optional = true;
+
+    let mut threads = value("--threads", "u32", "The number of threads");
+    threads.optional = true;
+
+    let mut circle = ArgSpec::new(
+        "--shape-circle",
+        ArgKind::Group(vec![ArgSpec::value("--shape-circle-radius", "FLOAT")]),
+    );
+    circle.doc = "A circle".to_string();
+    let mut square = ArgSpec::new("--shape-square", ArgKind::Switch);
+    square.doc = "A square".to_string();
+    let mut shape = ArgSpec::new("--shape", ArgKind::Alternatives(vec![circle, square]));
+    shape.doc = "The shape".to_string();
+
+    let mut files = value("FILES", "PATH", "The files to read");
+    files.optional = true;
+    files.repeatable = true;
+    files.positional = true;
+
+    assert_eq!(
+        ArgSpec::new("", ArgKind::Group(vec![verbose, threads, shape, files])),
+        Opt::spec("")
+    );
+}
+
+#[test]
+fn builtin_spec() {
+    assert_eq!(ArgSpec::value("--n", "u8"), u8::spec("--n"));
+    let mut v = ArgSpec::value("--name", "STRING");
+    v.optional = true;
+    assert_eq!(v, Option::<String>::spec("--name"));
+    v.repeatable = true;
+    assert_eq!(v, Vec::<String>::spec("--name"));
+    assert_eq!(ArgSpec::value("", "(true|false)"), bool::spec(""));
+}
+
+#[test]
+fn subcommand_spec() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    #[auto_args(subcommand)]
+    enum Command {
+        /// Build it
+        Build { release: bool },
+        /// Print the version
+        Version,
+    }
+    let spec = Command::spec("");
+    assert_eq!("COMMAND", spec.name);
+    assert!(spec.positional);
+    match spec.kind {
+        ArgKind::Alternatives(ref commands) => {
+            let names: Vec<_> = commands.iter().map(|c| c.name.as_str()).collect();
+            assert_eq!(vec!["build", "version"], names);
+            assert_eq!("Build it", commands[0].doc);
+            match commands[0].kind {
+                ArgKind::Group(ref flags) => assert_eq!("--release", flags[0].name),
+                ref k => panic!("expected a group, not {:?}", k),
+            }
+        }
+        ref k => panic!("expected alternatives, not {:?}", k),
+    }
+}
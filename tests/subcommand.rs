@@ -0,0 +1,193 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::{AutoArgs, Error};
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Files {
+    /// The files to remove
+    #[auto_args(positional)]
+    files: Vec<String>,
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+#[auto_args(subcommand)]
+enum Command {
+    /// Build the project
+    Build {
+        /// Build with optimizations
+        #[auto_args(short = 'r')]
+        release: bool,
+        /// The number of jobs
+        jobs: Option<u32>,
+    },
+    /// Remove build products
+    Clean(Files),
+    /// Print the version
+    Version,
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Opt {
+    /// Be verbose
+    verbose: bool,
+    command: Command,
+}
+
+#[test]
+fn subcommand() {
+    println!("help: {}", Opt::help());
+    println!("usage: {}", Opt::usage());
+    assert!(Opt::usage().contains(" [--verbose] COMMAND\n"));
+    assert!(Opt::usage().contains("\nCOMMANDS:\n"));
+    assert!(Opt::help().contains("\nCOMMANDS:\n"));
+    assert!(Opt::help().contains("  build   Build the project\n"));
+    assert!(Opt::help().contains("  clean   Remove build products\n"));
+    assert!(Opt::help().contains("  version Print the version\n"));
+    assert!(Opt::help().contains("build -r, [--release]"));
+    assert!(Opt::help().contains("clean FILES"));
+
+    assert_eq!(
+        Opt {
+            verbose: false,
+            command: Command::Build {
+                release: true,
+                jobs: None
+            },
+        },
+        Opt::from_iter(["", "build", "--release"]).unwrap()
+    );
+    assert_eq!(
+        Opt {
+            verbose: true,
+            command: Command::Build {
+                release: true,
+                jobs: Some(4)
+            },
+        },
+        Opt::from_iter(["", "--verbose", "build", "-r", "--jobs", "4"]).unwrap()
+    );
+    assert_eq!(
+        Opt {
+            verbose: true,
+            command: Command::Clean(Files {
+                files: vec!["a".to_string(), "b".to_string()]
+            }),
+        },
+        Opt::from_iter(["", "clean", "a", "--verbose", "b"]).unwrap()
+    );
+    assert_eq!(
+        Opt {
+            verbose: false,
+            command: Command::Version,
+        },
+        Opt::from_iter(["", "version"]).unwrap()
+    );
+
+    assert_eq!(
+        Err(Error::MissingArgument {
+            name: "COMMAND".to_string()
+        }),
+        Opt::from_iter(["", "--verbose"])
+    );
+    assert_eq!(
+        Err(Error::UnexpectedArgument {
+            value: "frobnicate".to_string(),
+            index: Some(1)
+        }),
+        Opt::from_iter(["", "frobnicate"])
+    );
+    assert_eq!(
+        Err(Error::UnexpectedOption {
+            option: "--release".to_string(),
+            index: Some(1),
+            suggestion: None
+        }),
+        Opt::from_iter(["", "--release", "version"])
+    );
+    assert_eq!(
+        Err(Error::UnexpectedArgument {
+            value: "extra".to_string(),
+            index: Some(2)
+        }),
+        Opt::from_iter(["", "version", "extra"])
+    );
+}
+
+#[test]
+fn positional_before_the_command() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct PosSub {
+        #[auto_args(positional)]
+        file: String,
+        command: Command,
+    }
+    let expected = PosSub {
+        file: "f.txt".to_string(),
+        command: Command::Build {
+            release: true,
+            jobs: None,
+        },
+    };
+    assert_eq!(
+        Ok(&expected),
+        PosSub::from_iter(["", "f.txt", "build", "-r"]).as_ref()
+    );
+    assert_eq!(
+        Ok(&expected),
+        PosSub::from_iter(["", "build", "-r", "f.txt"]).as_ref()
+    );
+    assert_eq!(
+        Err(Error::UnexpectedArgument {
+            value: "frobnicate".to_string(),
+            index: Some(1)
+        }),
+        PosSub::from_iter(["", "frobnicate", "f.txt"])
+    );
+}
+
+#[test]
+fn options_before_the_command() {
+    #[derive(AutoArgs, PartialEq, Debug)]
+    struct Top {
+        command: Command,
+        /// The configuration file
+        config: String,
+    }
+    let expected = Top {
+        command: Command::Build {
+            release: false,
+            jobs: Some(2),
+        },
+        config: "foo".to_string(),
+    };
+    assert_eq!(
+        Ok(&expected),
+        Top::from_iter(["", "--config", "foo", "build", "--jobs", "2"]).as_ref()
+    );
+    assert_eq!(
+        Ok(&expected),
+        Top::from_iter(["", "build", "--jobs", "2", "--config", "foo"]).as_ref()
+    );
+    assert_eq!(
+        Err(Error::UnexpectedOption {
+            option: "--jobs".to_string(),
+            index: Some(3),
+            suggestion: None
+        }),
+        Top::from_iter(["", "--config", "foo", "--jobs", "2", "build"])
+    );
+    assert_eq!(
+        Err(Error::UnexpectedOption {
+            option: "-r".to_string(),
+            index: Some(3),
+            suggestion: None
+        }),
+        Top::from_iter(["", "--config", "foo", "-r", "build"])
+    );
+}
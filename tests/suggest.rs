@@ -0,0 +1,63 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::{AutoArgs, Error};
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Position {
+    x: f64,
+    y: f64,
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+enum Shape {
+    Circle { radius: f64 },
+    Square(f64),
+}
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Opt {
+    verbose: bool,
+    position: Option<Position>,
+    shape: Option<Shape>,
+}
+
+fn suggestion(args: &[&str]) -> Option<String> {
+    match Opt::from_iter(args.iter().cloned()) {
+        Err(Error::UnexpectedOption { suggestion, .. }) => suggestion,
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn suggest_flags() {
+    assert_eq!(Some("--verbose".to_string()), suggestion(&["", "--verbos"]));
+    assert_eq!(Some("--verbose".to_string()), suggestion(&["", "--vrebose"]));
+    assert_eq!(
+        Some("--position-x".to_string()),
+        suggestion(&["", "--postion-x", "1"])
+    );
+    assert_eq!(
+        Some("--shape-circle-radius".to_string()),
+        suggestion(&["", "--shape-circle-raduis=2"])
+    );
+    assert_eq!(
+        Some("--shape-square".to_string()),
+        suggestion(&["", "--shape-sqare", "2"])
+    );
+    assert_eq!(None, suggestion(&["", "--frobnicate"]));
+
+    let err = Opt::from_iter(["", "--verbos"]).unwrap_err();
+    println!("error: {}", err);
+    assert_eq!(
+        "unexpected option `--verbos`
+  --> argument 1
+   = help: did you mean `--verbose`?",
+        err.to_string()
+    );
+}
@@ -0,0 +1,59 @@
+// Copyright 2018 David Roundy <roundyd@physics.oregonstate.edu>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use auto_args::AutoArgs;
+use std::ffi::OsString;
+
+#[derive(AutoArgs, PartialEq, Debug)]
+struct Wrapper {
+    /// Be verbose
+    verbose: bool,
+    /// The program to run
+    #[auto_args(positional)]
+    program: String,
+    /// Arguments for the program
+    #[auto_args(trailing)]
+    args: Vec<OsString>,
+}
+
+fn os(args: &[&str]) -> Vec<OsString> {
+    args.iter().map(OsString::from).collect()
+}
+
+#[test]
+fn trailing_arguments() {
+    println!("help: {}", Wrapper::help());
+    assert!(Wrapper::usage().contains(" [--verbose] PROGRAM [-- ARGS...]\n"));
+    assert!(Wrapper::help().contains("[-- ARGS...] Arguments for the program\n"));
+
+    assert_eq!(
+        Wrapper {
+            verbose: false,
+            program: "ls".to_string(),
+            args: vec![],
+        },
+        Wrapper::from_iter(["", "ls"]).unwrap()
+    );
+    assert_eq!(
+        Wrapper {
+            verbose: true,
+            program: "ls".to_string(),
+            args: os(&["-l", "--verbose", "--", "dir"]),
+        },
+        Wrapper::from_iter(["", "ls", "--verbose", "--", "-l", "--verbose", "--", "dir"]).unwrap()
+    );
+    assert_eq!(
+        Wrapper {
+            verbose: false,
+            program: "ls".to_string(),
+            args: vec![],
+        },
+        Wrapper::from_iter(["", "ls", "--"]).unwrap()
+    );
+    assert!(Wrapper::from_iter(["", "--", "ls"]).is_err());
+}
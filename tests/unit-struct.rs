@@ -6,6 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![allow(redundant_semicolons, unused_imports, clippy::needless_borrows_for_generic_args)]
+
 #[macro_use]
 extern crate auto_args;
 